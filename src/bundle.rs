@@ -0,0 +1,518 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::{Pool, Sqlite};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use crate::config_manager::ConfigManager;
+use crate::database;
+use crate::handlers::tasks;
+use crate::models::{RcloneConfig, Task};
+
+/// Current bundle format version
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Number of PBKDF2 rounds used to derive the secret encryption key
+const PBKDF2_ROUNDS: u32 = 600_000;
+
+/// Most PBKDF2 rounds accepted from a bundle, the count is chosen by whoever made the bundle
+const MAX_PBKDF2_ROUNDS: u32 = 2_000_000;
+
+/// Prefix marking an encrypted secret value inside a bundle
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// rclone config keys that hold credentials and are encrypted when a passphrase is given
+const SECRET_KEYS: &[&str] = &[
+    "pass",
+    "password2",
+    "client_secret",
+    "token",
+    "secret_access_key",
+    "session_token",
+    "key",
+    "sas_url",
+    "bearer_token",
+    "service_account_credentials",
+];
+
+/// Portable export of all remotes and tasks of an instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bundle {
+    pub version: u32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub encryption: Option<BundleEncryption>,
+    pub remotes: Vec<RcloneConfig>,
+    pub tasks: Vec<Task>,
+}
+
+/// Key derivation parameters for encrypted secrets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleEncryption {
+    pub kdf: String,
+    pub rounds: u32,
+    pub salt: String,
+}
+
+/// How to handle remotes and tasks that already exist on import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    #[default]
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    Create,
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportItem {
+    pub name: String,
+    pub action: ImportAction,
+    pub new_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub remotes: Vec<ImportItem>,
+    pub tasks: Vec<ImportItem>,
+}
+
+/// Build a bundle from the current configs and tasks, encrypting secrets if a passphrase is given
+pub async fn export_bundle(
+    config_manager: &ConfigManager,
+    pool: &Pool<Sqlite>,
+    passphrase: Option<&str>,
+) -> anyhow::Result<Bundle> {
+    let mut remotes = config_manager.load_configs().await?;
+    remotes.sort_by(|a, b| a.name.cmp(&b.name));
+    let tasks = database::get_all_tasks(pool).await?;
+
+    let encryption = match passphrase.filter(|p| !p.is_empty()) {
+        Some(passphrase) => {
+            let mut salt = [0u8; 16];
+            OsRng.fill_bytes(&mut salt);
+            let encryption = BundleEncryption {
+                kdf: "pbkdf2-sha256".to_string(),
+                rounds: PBKDF2_ROUNDS,
+                salt: BASE64.encode(salt),
+            };
+            let cipher = derive_cipher(passphrase, &encryption)?;
+            for remote in remotes.iter_mut() {
+                map_secrets(remote, |value| encrypt_value(&cipher, value))?;
+            }
+            Some(encryption)
+        }
+        None => None,
+    };
+
+    Ok(Bundle {
+        version: BUNDLE_VERSION,
+        created_at: chrono::Utc::now(),
+        encryption,
+        remotes,
+        tasks,
+    })
+}
+
+/// Apply a bundle to the current instance using the given merge strategy
+pub async fn import_bundle(
+    config_manager: &ConfigManager,
+    pool: &Pool<Sqlite>,
+    mut bundle: Bundle,
+    strategy: MergeStrategy,
+    passphrase: Option<&str>,
+    dry_run: bool,
+) -> anyhow::Result<ImportReport> {
+    if bundle.version > BUNDLE_VERSION {
+        return Err(anyhow::anyhow!("Unsupported bundle version: {}", bundle.version));
+    }
+
+    // Decrypt everything up front so a wrong passphrase never leaves a half-applied import
+    if let Some(encryption) = &bundle.encryption {
+        let passphrase = passphrase
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Bundle contains encrypted secrets, a passphrase is required"))?;
+        let cipher = derive_cipher(passphrase, encryption)?;
+        for remote in bundle.remotes.iter_mut() {
+            map_secrets(remote, |value| decrypt_value(&cipher, value))?;
+        }
+    }
+
+    // Tasks are checked like tasks created through the API, before anything is changed
+    for task in bundle.tasks.iter_mut() {
+        task.bwlimit = tasks::task_bwlimit(task.bwlimit.take())
+            .map_err(|e| anyhow::anyhow!("Invalid bandwidth limit of task {}: {}", task.name, e))?;
+        task.retry = tasks::task_retry(task.retry.take())
+            .map_err(|e| anyhow::anyhow!("Invalid retry policy of task {}: {}", task.name, e))?;
+    }
+
+    let remote_names: HashSet<String> = config_manager
+        .load_configs()
+        .await?
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    let (remotes, renamed_remotes) = plan_remotes(bundle.remotes, strategy, remote_names);
+
+    let task_names: HashSet<String> = database::get_all_tasks(pool)
+        .await?
+        .into_iter()
+        .map(|t| t.name)
+        .collect();
+    let tasks = plan_tasks(bundle.tasks, strategy, task_names, &renamed_remotes);
+
+    let report = ImportReport {
+        dry_run,
+        remotes: remotes.iter().map(|(_, item)| item.clone()).collect(),
+        tasks: tasks.iter().map(|(_, item)| item.clone()).collect(),
+    };
+    if dry_run {
+        return Ok(report);
+    }
+
+    for (remote, item) in remotes {
+        match item.action {
+            ImportAction::Skip => {}
+            ImportAction::Overwrite => {
+                config_manager.delete_config(&remote.name).await?;
+                config_manager.store_config(remote).await?;
+            }
+            ImportAction::Create | ImportAction::Rename => {
+                config_manager.store_config(remote).await?;
+            }
+        }
+    }
+
+    for (mut task, item) in tasks {
+        match item.action {
+            ImportAction::Skip => {}
+            ImportAction::Overwrite => {
+                // Updated in place, so everything keyed by the task id (e.g. notifications) is kept
+                match database::get_task_by_name(pool, &task.name).await? {
+                    Some(existing) => {
+                        task.id = existing.id;
+                        database::update_task(pool, &task).await?;
+                    }
+                    None => database::create_task(pool, &task).await?,
+                }
+            }
+            ImportAction::Create | ImportAction::Rename => {
+                database::create_task(pool, &task).await?;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Decide what happens to each remote of a bundle. Returns the remotes under their
+/// final names and the renamed remotes (old name to new name).
+fn plan_remotes(
+    remotes: Vec<RcloneConfig>,
+    strategy: MergeStrategy,
+    mut taken: HashSet<String>,
+) -> (Vec<(RcloneConfig, ImportItem)>, HashMap<String, String>) {
+    let mut renamed = HashMap::new();
+    let planned = remotes
+        .into_iter()
+        .map(|mut remote| {
+            let item = plan_item(&remote.name, taken.contains(&remote.name), strategy, &taken);
+            if let Some(new_name) = &item.new_name {
+                renamed.insert(remote.name.clone(), new_name.clone());
+                remote.name = new_name.clone();
+            }
+            taken.insert(remote.name.clone());
+            (remote, item)
+        })
+        .collect();
+
+    (planned, renamed)
+}
+
+/// Decide what happens to each task of a bundle. Tasks get new ids and keep pointing
+/// at their remote if it was imported under a new name.
+fn plan_tasks(
+    tasks: Vec<Task>,
+    strategy: MergeStrategy,
+    mut taken: HashSet<String>,
+    renamed_remotes: &HashMap<String, String>,
+) -> Vec<(Task, ImportItem)> {
+    tasks
+        .into_iter()
+        .map(|mut task| {
+            let item = plan_item(&task.name, taken.contains(&task.name), strategy, &taken);
+            if let Some(new_name) = &item.new_name {
+                task.name = new_name.clone();
+            }
+            if let Some(new_remote) = renamed_remotes.get(&task.remote_name) {
+                task.remote_name = new_remote.clone();
+            }
+            task.id = Uuid::new_v4().to_string();
+            taken.insert(task.name.clone());
+            (task, item)
+        })
+        .collect()
+}
+
+/// Decide what happens to a single remote or task on import
+fn plan_item(name: &str, exists: bool, strategy: MergeStrategy, taken: &HashSet<String>) -> ImportItem {
+    if !exists {
+        return ImportItem {
+            name: name.to_string(),
+            action: ImportAction::Create,
+            new_name: None,
+        };
+    }
+
+    match strategy {
+        MergeStrategy::Skip => ImportItem {
+            name: name.to_string(),
+            action: ImportAction::Skip,
+            new_name: None,
+        },
+        MergeStrategy::Overwrite => ImportItem {
+            name: name.to_string(),
+            action: ImportAction::Overwrite,
+            new_name: None,
+        },
+        MergeStrategy::Rename => {
+            let new_name = (2..)
+                .map(|n| format!("{}-{}", name, n))
+                .find(|candidate| !taken.contains(candidate))
+                .unwrap_or_default();
+            ImportItem {
+                name: name.to_string(),
+                action: ImportAction::Rename,
+                new_name: Some(new_name),
+            }
+        }
+    }
+}
+
+/// Run a transformation over every secret value of a remote
+fn map_secrets<F>(remote: &mut RcloneConfig, f: F) -> anyhow::Result<()>
+where
+    F: Fn(&str) -> anyhow::Result<String>,
+{
+    if let Some(password) = &remote.password {
+        if !password.is_empty() {
+            remote.password = Some(f(password)?);
+        }
+    }
+
+    for (key, value) in remote.additional_fields.iter_mut() {
+        if SECRET_KEYS.contains(&key.as_str()) && !value.is_empty() {
+            *value = f(value)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn derive_cipher(passphrase: &str, encryption: &BundleEncryption) -> anyhow::Result<Aes256Gcm> {
+    if encryption.kdf != "pbkdf2-sha256" {
+        return Err(anyhow::anyhow!("Unsupported key derivation: {}", encryption.kdf));
+    }
+    if encryption.rounds == 0 || encryption.rounds > MAX_PBKDF2_ROUNDS {
+        return Err(anyhow::anyhow!(
+            "Unsupported number of key derivation rounds: {} (at most {})",
+            encryption.rounds, MAX_PBKDF2_ROUNDS
+        ));
+    }
+
+    let salt = BASE64
+        .decode(&encryption.salt)
+        .map_err(|e| anyhow::anyhow!("Invalid bundle salt: {}", e))?;
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), &salt, encryption.rounds, &mut key);
+
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+fn encrypt_value(cipher: &Aes256Gcm, value: &str) -> anyhow::Result<String> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, value.as_bytes())
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt secret"))?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(payload)))
+}
+
+fn decrypt_value(cipher: &Aes256Gcm, value: &str) -> anyhow::Result<String> {
+    let encoded = value
+        .strip_prefix(ENCRYPTED_PREFIX)
+        .ok_or_else(|| anyhow::anyhow!("Secret is not encrypted"))?;
+    let payload = BASE64
+        .decode(encoded)
+        .map_err(|e| anyhow::anyhow!("Invalid encrypted secret: {}", e))?;
+
+    if payload.len() < 12 {
+        return Err(anyhow::anyhow!("Invalid encrypted secret"));
+    }
+
+    let (nonce, ciphertext) = payload.split_at(12);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Wrong passphrase or corrupted bundle"))?;
+
+    String::from_utf8(plaintext).map_err(|e| anyhow::anyhow!("Invalid decrypted secret: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encryption() -> BundleEncryption {
+        // Few rounds keep the tests fast, the format is the same
+        BundleEncryption {
+            kdf: "pbkdf2-sha256".to_string(),
+            rounds: 1_000,
+            salt: BASE64.encode([7u8; 16]),
+        }
+    }
+
+    fn remote(name: &str) -> RcloneConfig {
+        RcloneConfig {
+            name: name.to_string(),
+            config_type: "s3".to_string(),
+            url: None,
+            username: Some("user".to_string()),
+            password: Some("obscured".to_string()),
+            additional_fields: HashMap::from([
+                ("secret_access_key".to_string(), "s3cr3t".to_string()),
+                ("region".to_string(), "eu-west-1".to_string()),
+            ]),
+        }
+    }
+
+    fn task(name: &str, remote_name: &str) -> Task {
+        Task {
+            id: "original-id".to_string(),
+            name: name.to_string(),
+            source_path: "/data".to_string(),
+            remote_name: remote_name.to_string(),
+            remote_path: "/backup".to_string(),
+            chunk_size: None,
+            use_chunking: false,
+            bwlimit: None,
+            resume_interrupted: false,
+            retry: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    fn names(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn secrets_round_trip() {
+        let cipher = derive_cipher("correct horse", &encryption()).unwrap();
+        let mut config = remote("s3");
+
+        map_secrets(&mut config, |value| encrypt_value(&cipher, value)).unwrap();
+        assert!(config.password.as_deref().unwrap().starts_with(ENCRYPTED_PREFIX));
+        assert!(config.additional_fields["secret_access_key"].starts_with(ENCRYPTED_PREFIX));
+        assert_eq!(config.additional_fields["region"], "eu-west-1");
+        assert_eq!(config.username.as_deref(), Some("user"));
+
+        map_secrets(&mut config, |value| decrypt_value(&cipher, value)).unwrap();
+        assert_eq!(config.password.as_deref(), Some("obscured"));
+        assert_eq!(config.additional_fields["secret_access_key"], "s3cr3t");
+    }
+
+    #[test]
+    fn wrong_passphrase_is_rejected() {
+        let cipher = derive_cipher("correct horse", &encryption()).unwrap();
+        let encrypted = encrypt_value(&cipher, "s3cr3t").unwrap();
+
+        let wrong = derive_cipher("battery staple", &encryption()).unwrap();
+        let error = decrypt_value(&wrong, &encrypted).unwrap_err();
+        assert_eq!(error.to_string(), "Wrong passphrase or corrupted bundle");
+    }
+
+    #[test]
+    fn plain_value_is_not_decrypted() {
+        let cipher = derive_cipher("correct horse", &encryption()).unwrap();
+        assert!(decrypt_value(&cipher, "s3cr3t").is_err());
+    }
+
+    #[test]
+    fn excessive_rounds_are_rejected() {
+        let mut encryption = encryption();
+        encryption.rounds = u32::MAX;
+        assert!(derive_cipher("correct horse", &encryption).is_err());
+
+        encryption.rounds = 0;
+        assert!(derive_cipher("correct horse", &encryption).is_err());
+    }
+
+    #[test]
+    fn plan_item_follows_strategy() {
+        let taken = names(&["backup"]);
+
+        let item = plan_item("photos", false, MergeStrategy::Skip, &taken);
+        assert_eq!((item.action, item.new_name), (ImportAction::Create, None));
+
+        let item = plan_item("backup", true, MergeStrategy::Skip, &taken);
+        assert_eq!((item.action, item.new_name), (ImportAction::Skip, None));
+
+        let item = plan_item("backup", true, MergeStrategy::Overwrite, &taken);
+        assert_eq!((item.action, item.new_name), (ImportAction::Overwrite, None));
+
+        let item = plan_item("backup", true, MergeStrategy::Rename, &taken);
+        assert_eq!((item.action, item.new_name.as_deref()), (ImportAction::Rename, Some("backup-2")));
+    }
+
+    #[test]
+    fn rename_skips_taken_names() {
+        let item = plan_item("backup", true, MergeStrategy::Rename, &names(&["backup", "backup-2", "backup-3"]));
+        assert_eq!(item.new_name.as_deref(), Some("backup-4"));
+    }
+
+    #[test]
+    fn renamed_remotes_are_carried_into_tasks() {
+        let (remotes, renamed) = plan_remotes(
+            vec![remote("s3"), remote("gdrive")],
+            MergeStrategy::Rename,
+            names(&["s3"]),
+        );
+        assert_eq!(remotes[0].0.name, "s3-2");
+        assert_eq!(remotes[1].0.name, "gdrive");
+        assert_eq!(renamed, HashMap::from([("s3".to_string(), "s3-2".to_string())]));
+
+        let tasks = plan_tasks(
+            vec![task("nightly", "s3"), task("photos", "gdrive")],
+            MergeStrategy::Rename,
+            names(&["nightly"]),
+            &renamed,
+        );
+        assert_eq!(tasks[0].0.name, "nightly-2");
+        assert_eq!(tasks[0].0.remote_name, "s3-2");
+        assert_eq!(tasks[0].1.action, ImportAction::Rename);
+        assert_eq!(tasks[1].0.remote_name, "gdrive");
+        assert_eq!(tasks[1].1.action, ImportAction::Create);
+        assert!(tasks.iter().all(|(task, _)| task.id != "original-id"));
+    }
+
+    #[test]
+    fn duplicate_names_in_bundle_are_renamed() {
+        let (remotes, _) = plan_remotes(vec![remote("s3"), remote("s3")], MergeStrategy::Rename, HashSet::new());
+        assert_eq!(remotes[0].1.action, ImportAction::Create);
+        assert_eq!(remotes[1].0.name, "s3-2");
+    }
+}
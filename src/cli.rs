@@ -0,0 +1,820 @@
+use axum::{extract::{Json, Path}, response::Json as ResponseJson, Extension};
+use clap::Subcommand;
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::bundle::{self, MergeStrategy};
+use crate::client::ApiClient;
+use crate::config_manager::ConfigManager;
+use crate::database;
+use crate::handlers;
+use crate::models::{ApiResponse, BwLimitRequest, ConfigRequest, LogQuery, LogLevel, RetryPolicy, StartTaskRequest, SyncProgress, Task, TaskRequest};
+use crate::notifications;
+use crate::settings::Settings;
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Start the web server (default)
+    Serve,
+    /// Manage sync tasks
+    #[command(subcommand)]
+    Task(TaskCommand),
+    /// Manage rclone remotes
+    #[command(subcommand)]
+    Remote(RemoteCommand),
+    /// Inspect and control sync jobs
+    #[command(subcommand)]
+    Job(JobCommand),
+    /// Export and import remotes and tasks
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Subcommand)]
+pub enum TaskCommand {
+    /// List all tasks
+    List,
+    /// Create a new task
+    Create {
+        name: String,
+        #[arg(long, help = "Local source path")]
+        source: String,
+        #[arg(long, help = "Target remote name")]
+        remote: String,
+        #[arg(long, default_value = "/", help = "Target path on the remote")]
+        remote_path: String,
+        #[arg(long, help = "Chunk size, e.g. 8M, 16M, 32M")]
+        chunk_size: Option<String>,
+        #[arg(long, help = "Enable chunked uploads")]
+        chunking: bool,
+        #[arg(long, help = "Bandwidth limit, e.g. 10M or \"08:00,512k 19:00,off\" [default: global --bwlimit]")]
+        bwlimit: Option<String>,
+        #[arg(long, help = "Restart jobs interrupted by a server restart")]
+        resume_interrupted: bool,
+        #[arg(long, help = "Attempts of a job when rclone fails as a whole, including the first one")]
+        retry_attempts: Option<u32>,
+        #[arg(long, help = "Delays before the retries, e.g. 1m,5m,30m; the last one repeats [default: 60s,5m,30m]")]
+        retry_backoff: Option<String>,
+        #[arg(long, value_delimiter = ',', help = "rclone exit codes that are retried [default: 1,5]")]
+        retry_exit_codes: Vec<i32>,
+    },
+    /// Change an existing task; omitted options keep their current value
+    Edit {
+        name: String,
+        #[arg(long, help = "Rename the task")]
+        new_name: Option<String>,
+        #[arg(long, help = "Local source path")]
+        source: Option<String>,
+        #[arg(long, help = "Target remote name")]
+        remote: Option<String>,
+        #[arg(long, help = "Target path on the remote")]
+        remote_path: Option<String>,
+        #[arg(long, help = "Chunk size, e.g. 8M, 16M, 32M")]
+        chunk_size: Option<String>,
+        #[arg(long, help = "Enable or disable chunked uploads")]
+        chunking: Option<bool>,
+        #[arg(long, help = "Bandwidth limit, an empty value removes the task's own limit")]
+        bwlimit: Option<String>,
+        #[arg(long, help = "Enable or disable restarting jobs interrupted by a server restart")]
+        resume_interrupted: Option<bool>,
+        #[arg(long, help = "Attempts of a job when rclone fails as a whole, 1 disables retries")]
+        retry_attempts: Option<u32>,
+        #[arg(long, help = "Delays before the retries, e.g. 1m,5m,30m; the last one repeats")]
+        retry_backoff: Option<String>,
+        #[arg(long, value_delimiter = ',', help = "rclone exit codes that are retried")]
+        retry_exit_codes: Vec<i32>,
+    },
+    /// Delete a task
+    Delete { name: String },
+    /// Run a task and wait for it to finish
+    Run { name: String },
+}
+
+#[derive(Subcommand)]
+pub enum RemoteCommand {
+    /// List all remotes
+    List,
+    /// Add or update a remote
+    Add {
+        name: String,
+        #[arg(long = "type", help = "Remote type, e.g. webdav-nextcloud, s3, drive")]
+        config_type: String,
+        #[arg(long)]
+        url: Option<String>,
+        #[arg(long)]
+        user: Option<String>,
+        #[arg(long, env = "RCLONE_GUI_REMOTE_PASSWORD", hide_env_values = true, help = "Password (will be obscured)")]
+        password: Option<String>,
+        #[arg(long = "field", value_parser = parse_key_value, help = "Additional rclone option as key=value (repeatable)")]
+        fields: Vec<(String, String)>,
+    },
+    /// Check that a remote is reachable
+    Test { name: String },
+    /// Delete a remote
+    Delete { name: String },
+}
+
+#[derive(Subcommand)]
+pub enum JobCommand {
+    /// List jobs of all rclone-gui processes
+    List,
+    /// Print the log of a job, or its parsed entries when filtered
+    Log {
+        job_id: String,
+        #[arg(long, value_enum, help = "Only entries of this level or more severe")]
+        level: Option<LogLevel>,
+        #[arg(long, help = "Only entries whose object (file) contains this text")]
+        object: Option<String>,
+        #[arg(long, help = "Only the last N matching entries")]
+        tail: Option<usize>,
+    },
+    /// List the files a job failed to transfer
+    Errors { job_id: String },
+    /// List the runs of rclone of a job, more than one if it was retried
+    Attempts { job_id: String },
+    /// Show or change the bandwidth limit of a running job
+    Bwlimit {
+        job_id: String,
+        #[arg(help = "New rate, e.g. 1M, 512k:2M (upload:download) or off")]
+        rate: Option<String>,
+    },
+    /// Cancel a running job
+    Cancel { job_id: String },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Export remotes and tasks into a portable bundle
+    Export {
+        #[arg(help = "File to write the bundle to")]
+        file: String,
+        #[arg(long, env = "RCLONE_GUI_BUNDLE_PASSPHRASE", hide_env_values = true, help = "Encrypt secrets with this passphrase")]
+        passphrase: Option<String>,
+    },
+    /// Import remotes and tasks from a bundle
+    Import {
+        #[arg(help = "Bundle file to import")]
+        file: String,
+        #[arg(long, value_enum, default_value_t = MergeStrategy::Skip, help = "What to do with remotes and tasks that already exist")]
+        strategy: MergeStrategy,
+        #[arg(long, help = "Only report what would be imported")]
+        dry_run: bool,
+        #[arg(long, env = "RCLONE_GUI_BUNDLE_PASSPHRASE", hide_env_values = true, help = "Passphrase for encrypted secrets")]
+        passphrase: Option<String>,
+    },
+}
+
+fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .ok_or_else(|| format!("expected key=value, got '{}'", s))
+}
+
+/// Output format of CLI commands
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable output
+    Text,
+    /// One JSON document per line (NDJSON); `task run` streams progress events
+    Json,
+}
+
+/// Print a handler response either as JSON or via the human-readable formatter.
+/// Exits with code 1 if the handler reported an error.
+fn output<T: Serialize>(json: bool, response: ResponseJson<ApiResponse<T>>, human: impl FnOnce(&T)) {
+    let response = response.0;
+
+    if json {
+        print_json(&response);
+        if !response.success {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    match (&response.data, response.success) {
+        (Some(data), true) => human(data),
+        _ => {
+            eprintln!("❌ {}", response.error.as_deref().unwrap_or("Unknown error"));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Print a value as a single JSON line
+fn print_json<T: Serialize>(value: &T) {
+    println!("{}", serde_json::to_string(value).expect("CLI output is always serializable"));
+}
+
+fn fail(json: bool, message: &str) -> ! {
+    if json {
+        print_json(&ApiResponse::<()>::error(message));
+    } else {
+        eprintln!("❌ {}", message);
+    }
+    std::process::exit(1);
+}
+
+/// Run a CLI subcommand through the same handlers the HTTP API uses
+pub async fn run(command: Command, db_pool: Pool<Sqlite>, settings: Arc<Settings>, json: bool) {
+    let config_manager = Arc::new(ConfigManager::new(false, &settings));
+
+    match command {
+        Command::Serve => unreachable!("serve is handled by main"),
+        Command::Task(command) => run_task_command(command, db_pool, settings, json).await,
+        Command::Remote(command) => run_remote_command(command, config_manager, json).await,
+        Command::Job(command) => run_job_command(command, db_pool, settings, json).await,
+        Command::Config(command) => run_config_command(command, config_manager, db_pool, json).await,
+    }
+}
+
+async fn run_task_command(command: TaskCommand, pool: Pool<Sqlite>, settings: Arc<Settings>, json: bool) {
+    match command {
+        TaskCommand::List => {
+            let response = handlers::tasks::get_tasks(Extension(pool)).await;
+            output(json, response, |tasks| {
+                if tasks.is_empty() {
+                    println!("No tasks found");
+                }
+                for task in tasks {
+                    println!(
+                        "{:<30} {} -> {}:{}{}",
+                        task.name,
+                        task.source_path,
+                        task.remote_name,
+                        task.remote_path,
+                        task.chunk_size.as_ref().map(|c| format!(" (chunk size {})", c)).unwrap_or_default()
+                    );
+                }
+            });
+        }
+        TaskCommand::Create { name, source, remote, remote_path, chunk_size, chunking, bwlimit, resume_interrupted, retry_attempts, retry_backoff, retry_exit_codes } => {
+            let retry_backoff = retry_backoff.map(|backoff| parse_backoff(&backoff).unwrap_or_else(|e| fail(json, &e)));
+            let request = TaskRequest {
+                name: name.clone(),
+                source_path: source,
+                remote_name: remote,
+                remote_path,
+                chunk_size,
+                use_chunking: Some(chunking),
+                bwlimit,
+                resume_interrupted: Some(resume_interrupted),
+                retry: retry_policy(None, retry_attempts, retry_backoff, retry_exit_codes),
+            };
+            let response = handlers::tasks::create_task(Extension(pool), Json(request)).await;
+            output(json, response, |id| println!("✅ Task '{}' created with ID {}", name, id));
+        }
+        TaskCommand::Edit { name, new_name, source, remote, remote_path, chunk_size, chunking, bwlimit, resume_interrupted, retry_attempts, retry_backoff, retry_exit_codes } => {
+            let retry_backoff = retry_backoff.map(|backoff| parse_backoff(&backoff).unwrap_or_else(|e| fail(json, &e)));
+            let task = match database::get_task_by_name(&pool, &name).await {
+                Ok(Some(task)) => task,
+                Ok(None) => fail(json, &format!("Task '{}' not found", name)),
+                Err(e) => fail(json, &format!("Failed to retrieve task '{}': {}", name, e)),
+            };
+            let request = TaskRequest {
+                name: new_name.unwrap_or(task.name),
+                source_path: source.unwrap_or(task.source_path),
+                remote_name: remote.unwrap_or(task.remote_name),
+                remote_path: remote_path.unwrap_or(task.remote_path),
+                chunk_size: chunk_size.or(task.chunk_size),
+                use_chunking: Some(chunking.unwrap_or(task.use_chunking)),
+                bwlimit: bwlimit.or(task.bwlimit),
+                resume_interrupted: Some(resume_interrupted.unwrap_or(task.resume_interrupted)),
+                retry: retry_policy(task.retry, retry_attempts, retry_backoff, retry_exit_codes),
+            };
+            let response = handlers::tasks::update_task(Extension(pool), Path(task.id), Json(request)).await;
+            output(json, response, |_| println!("✅ Task '{}' updated", name));
+        }
+        TaskCommand::Delete { name } => {
+            let task = match database::get_task_by_name(&pool, &name).await {
+                Ok(Some(task)) => task,
+                Ok(None) => fail(json, &format!("Task '{}' not found", name)),
+                Err(e) => fail(json, &format!("Failed to retrieve task '{}': {}", name, e)),
+            };
+            let response = handlers::tasks::delete_task(Extension(pool), Path(task.id)).await;
+            output(json, response, |_| println!("✅ Task '{}' deleted", name));
+        }
+        TaskCommand::Run { name } => run_task(TaskRunner::Local { pool, settings }, name, json).await,
+    }
+}
+
+/// Apply the retry options of `task create|edit` to the current retry policy of a task
+fn retry_policy(current: Option<RetryPolicy>, attempts: Option<u32>, backoff: Option<Vec<u64>>, exit_codes: Vec<i32>) -> Option<RetryPolicy> {
+    if attempts.is_none() && backoff.is_none() && exit_codes.is_empty() {
+        return current;
+    }
+    let mut retry = current.unwrap_or_else(|| RetryPolicy::new(1));
+    if let Some(attempts) = attempts {
+        retry.max_attempts = attempts;
+    }
+    if let Some(backoff) = backoff {
+        retry.backoff_secs = backoff;
+    }
+    if !exit_codes.is_empty() {
+        retry.exit_codes = exit_codes;
+    }
+    Some(retry)
+}
+
+/// Parse a list of delays like `30s,5m,1h` into seconds, plain numbers are seconds
+fn parse_backoff(value: &str) -> Result<Vec<u64>, String> {
+    value
+        .split(',')
+        .map(|delay| {
+            let delay = delay.trim();
+            let (number, unit) = delay.split_at(delay.find(|c: char| !c.is_ascii_digit()).unwrap_or(delay.len()));
+            let factor = match unit {
+                "" | "s" => 1,
+                "m" => 60,
+                "h" => 60 * 60,
+                _ => return Err(format!("Invalid delay '{}', use s, m or h", delay)),
+            };
+            number.parse::<u64>().map(|n| n * factor).map_err(|_| format!("Invalid delay '{}'", delay))
+        })
+        .collect()
+}
+
+async fn run_remote_command(command: RemoteCommand, config_manager: Arc<ConfigManager>, json: bool) {
+    match command {
+        RemoteCommand::List => {
+            let response = handlers::config::get_configs(Extension(config_manager)).await;
+            output(json, response, |configs| {
+                if configs.is_empty() {
+                    println!("No remotes found");
+                }
+                let mut configs: Vec<_> = configs.iter().collect();
+                configs.sort_by(|a, b| a.name.cmp(&b.name));
+                for config in configs {
+                    println!("{:<30} {:<20} {}", config.name, config.config_type, config.url.as_deref().unwrap_or(""));
+                }
+            });
+        }
+        RemoteCommand::Add { name, config_type, url, user, password, fields } => {
+            let request = ConfigRequest {
+                name: name.clone(),
+                config_type,
+                url,
+                username: user,
+                password,
+                additional_fields: Some(fields.into_iter().collect::<HashMap<_, _>>()),
+            };
+            let response = handlers::config::save_config(Extension(config_manager), Json(request)).await;
+            output(json, response, |_| println!("✅ Remote '{}' saved", name));
+        }
+        RemoteCommand::Test { name } => {
+            let response = handlers::config::test_config(Extension(config_manager), name).await;
+            output(json, response, |message| println!("✅ {}", message));
+        }
+        RemoteCommand::Delete { name } => {
+            let response = handlers::config::delete_config(Extension(config_manager), name.clone()).await;
+            output(json, response, |_| println!("✅ Remote '{}' deleted", name));
+        }
+    }
+}
+
+async fn run_job_command(command: JobCommand, pool: Pool<Sqlite>, settings: Arc<Settings>, json: bool) {
+    match command {
+        JobCommand::List => {
+            let response = handlers::sync::list_job_records(Extension(pool)).await;
+            output(json, response, |jobs| {
+                if jobs.is_empty() {
+                    println!("No jobs found");
+                }
+                for job in jobs {
+                    let started = chrono::DateTime::from_timestamp(job.start_time, 0)
+                        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_default();
+                    println!(
+                        "{}  {:<10} {:>5.1}%  {}  {} -> {}:{}{}{}",
+                        job.id,
+                        job.status,
+                        job.progress,
+                        started,
+                        job.source_path,
+                        job.remote_name,
+                        job.remote_path,
+                        job.task_name.as_ref().map(|t| format!(" [{}]", t)).unwrap_or_default(),
+                        if job.attempt > 1 { format!(" (attempt {})", job.attempt) } else { String::new() }
+                    );
+                }
+            });
+        }
+        JobCommand::Log { job_id, level: None, object: None, tail: None } => {
+            let response = handlers::sync::get_sync_log(Extension(settings), job_id).await;
+            output(json, response, |log| print!("{}", log));
+        }
+        JobCommand::Log { job_id, level, object, tail } => {
+            let query = LogQuery { level, object, tail, limit: Some(usize::MAX), ..Default::default() };
+            let response = handlers::sync::get_sync_log_entries(Extension(settings), job_id, query).await;
+            output(json, response, |page| {
+                for entry in &page.entries {
+                    println!(
+                        "{} {} {}{}",
+                        entry.time.map(|t| t.to_rfc3339()).unwrap_or_default(),
+                        format!("{:?}", entry.level).to_uppercase(),
+                        entry.object.as_deref().map(|o| format!("{}: ", o)).unwrap_or_default(),
+                        entry.msg,
+                    );
+                }
+            });
+        }
+        JobCommand::Errors { job_id } => {
+            let response = handlers::sync::get_sync_errors(Extension(pool), job_id).await;
+            output(json, response, |errors| {
+                if errors.is_empty() {
+                    println!("No failed files");
+                }
+                for error in errors {
+                    println!("{}\n    {} (retried {} times)", error.object, error.message, error.retries);
+                }
+            });
+        }
+        JobCommand::Bwlimit { job_id, rate: None } => {
+            let response = handlers::sync::get_job_bwlimit(Extension(pool), job_id).await;
+            output(json, response, |limit| println!("Bandwidth limit: {}", limit.rate));
+        }
+        JobCommand::Bwlimit { job_id, rate: Some(rate) } => {
+            let response = handlers::sync::set_job_bwlimit(Extension(pool), job_id, BwLimitRequest { rate }).await;
+            output(json, response, |limit| println!("✅ Bandwidth limit set to {}", limit.rate));
+        }
+        JobCommand::Attempts { job_id } => {
+            let response = handlers::sync::get_sync_attempts(Extension(pool), job_id).await;
+            output(json, response, |attempts| {
+                if attempts.is_empty() {
+                    println!("No attempts recorded");
+                }
+                for attempt in attempts {
+                    let started = chrono::DateTime::from_timestamp(attempt.start_time, 0)
+                        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_default();
+                    println!(
+                        "#{}  {}  {}{}",
+                        attempt.attempt,
+                        started,
+                        attempt.status,
+                        attempt.exit_code.map(|code| format!(" (rclone exit code {})", code)).unwrap_or_default()
+                    );
+                }
+            });
+        }
+        JobCommand::Cancel { job_id } => {
+            let response = handlers::sync::cancel_sync_job(Extension(pool), Extension(settings), job_id.clone()).await;
+            output(json, response, |_| println!("✅ Cancellation of job {} requested", job_id));
+        }
+    }
+}
+
+async fn run_config_command(command: ConfigCommand, config_manager: Arc<ConfigManager>, pool: Pool<Sqlite>, json: bool) {
+    match command {
+        ConfigCommand::Export { file, passphrase } => {
+            let request = handlers::bundle::ExportRequest { passphrase };
+            let response = handlers::bundle::export_bundle(Extension(config_manager), Extension(pool), Json(request)).await;
+
+            let Some(bundle) = response.0.data else {
+                fail(json, &format!("Failed to export bundle: {}", response.0.error.unwrap_or_default()));
+            };
+
+            let content = serde_json::to_string_pretty(&bundle).expect("Bundle is always serializable");
+            if let Err(e) = std::fs::write(&file, content) {
+                fail(json, &format!("Failed to write bundle to {}: {}", file, e));
+            }
+
+            let summary = format!("Exported {} remotes and {} tasks to {}", bundle.remotes.len(), bundle.tasks.len(), file);
+            output(json, ResponseJson(ApiResponse::success(summary)), |summary| {
+                println!("✅ {}", summary);
+                if bundle.encryption.is_none() {
+                    println!("⚠️  Secrets are only obscured, not encrypted (use --passphrase to encrypt them)");
+                }
+            });
+        }
+        ConfigCommand::Import { file, strategy, dry_run, passphrase } => {
+            let bundle = match std::fs::read_to_string(&file)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| serde_json::from_str::<bundle::Bundle>(&content).map_err(anyhow::Error::from))
+            {
+                Ok(bundle) => bundle,
+                Err(e) => fail(json, &format!("Failed to read bundle {}: {}", file, e)),
+            };
+
+            let request = handlers::bundle::ImportRequest { bundle, strategy, dry_run, passphrase };
+            let response = handlers::bundle::import_bundle(Extension(config_manager), Extension(pool), Json(request)).await;
+            output(json, response, |report| {
+                if report.dry_run {
+                    println!("🔍 Dry run - nothing was changed");
+                }
+                for (kind, items) in [("Remote", &report.remotes), ("Task", &report.tasks)] {
+                    for item in items {
+                        match &item.new_name {
+                            Some(new_name) => println!("   {} {}: {:?} -> {}", kind, item.name, item.action, new_name),
+                            None => println!("   {} {}: {:?}", kind, item.name, item.action),
+                        }
+                    }
+                }
+                println!("✅ Processed {} remotes and {} tasks", report.remotes.len(), report.tasks.len());
+            });
+        }
+    }
+}
+
+/// Result of `task run` / `--start-task`, reported as the process exit code
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunResult {
+    Success,
+    /// rclone (or the server) failed before anything could be transferred
+    Fatal,
+    NotFound,
+    SpawnFailed,
+    /// rclone ran to the end but some files could not be transferred
+    PartialErrors,
+    Cancelled,
+}
+
+impl RunResult {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            RunResult::Success => 0,
+            RunResult::Fatal => 1,
+            // 2 is used by clap for usage errors
+            RunResult::NotFound => 3,
+            RunResult::SpawnFailed => 4,
+            RunResult::PartialErrors => 5,
+            RunResult::Cancelled => 6,
+        }
+    }
+
+    /// Classify a finished job.
+    /// rclone exit codes 2 (usage), 3 (directory not found), 4 (file not found) and 7 (fatal)
+    /// mean the run could not be carried out; any other non-zero code is reported after
+    /// rclone has processed all files, so only some of them failed.
+    fn from_progress(progress: &SyncProgress) -> Self {
+        match progress.status.as_str() {
+            "Completed" => RunResult::Success,
+            "Cancelled" => RunResult::Cancelled,
+            status if handlers::sync::is_spawn_failure(status) => RunResult::SpawnFailed,
+            _ => match progress.exit_code {
+                None | Some(2 | 3 | 4 | 7) => RunResult::Fatal,
+                Some(_) => RunResult::PartialErrors,
+            },
+        }
+    }
+}
+
+/// NDJSON events printed by `task run --output json`
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum RunEvent<'a> {
+    Started {
+        task: &'a str,
+        job_id: &'a str,
+        source: &'a str,
+        remote: String,
+    },
+    Progress {
+        job_id: &'a str,
+        status: &'a str,
+        progress: f64,
+        transferred: u64,
+        total: u64,
+    },
+    Finished {
+        task: &'a str,
+        job_id: Option<&'a str>,
+        result: RunResult,
+        exit_code: i32,
+        status: &'a str,
+        rclone_exit_code: Option<i32>,
+        transferred: u64,
+        total: u64,
+        duration_secs: Option<i64>,
+    },
+}
+
+/// Where `task run` starts a job: in this process or on a running server
+pub enum TaskRunner {
+    Local { pool: Pool<Sqlite>, settings: Arc<Settings> },
+    Server(ApiClient),
+}
+
+impl TaskRunner {
+    async fn get_task(&self, task_name: &str) -> Result<Task, (RunResult, String)> {
+        let not_found = || (RunResult::NotFound, format!("Task '{}' not found", task_name));
+
+        match self {
+            TaskRunner::Local { pool, .. } => match database::get_task_by_name(pool, task_name).await {
+                Ok(Some(task)) => Ok(task),
+                Ok(None) => Err(not_found()),
+                Err(e) => Err((RunResult::Fatal, format!("Failed to retrieve task '{}': {}", task_name, e))),
+            },
+            TaskRunner::Server(client) => {
+                let response = client.get_tasks().await;
+                match response.data {
+                    Some(tasks) => tasks.into_iter().find(|t| t.name == task_name).ok_or_else(not_found),
+                    None => Err((RunResult::Fatal, format!(
+                        "Failed to retrieve task '{}': {}", task_name, response.error.unwrap_or_default()
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// Start the sync job through the handler behind POST /api/tasks/start
+    async fn start_task(&self, task_name: &str) -> ApiResponse<String> {
+        match self {
+            TaskRunner::Local { pool, settings } => {
+                let request = StartTaskRequest { task_name: task_name.to_string() };
+                handlers::tasks::start_task(Extension(pool.clone()), Extension(settings.clone()), Json(request)).await.0
+            }
+            TaskRunner::Server(client) => client.start_task(task_name).await,
+        }
+    }
+
+    async fn get_sync_progress(&self, job_id: &str) -> ApiResponse<SyncProgress> {
+        match self {
+            TaskRunner::Local { settings, .. } => {
+                handlers::sync::get_sync_progress(Extension(settings.clone()), job_id.to_string()).await.0
+            }
+            TaskRunner::Server(client) => client.get_sync_progress(job_id).await,
+        }
+    }
+}
+
+/// Polls in a row without progress information after which a job is considered lost
+const MAX_PROGRESS_FAILURES: u32 = 5;
+
+/// Report a run that ended before a job finished and exit with the matching code
+fn abort_run(json: bool, task_name: &str, job_id: Option<&str>, result: RunResult, message: &str) -> ! {
+    if json {
+        print_json(&RunEvent::Finished {
+            task: task_name,
+            job_id,
+            result,
+            exit_code: result.exit_code(),
+            status: message,
+            rclone_exit_code: None,
+            transferred: 0,
+            total: 0,
+            duration_secs: None,
+        });
+    } else {
+        eprintln!("❌ {}", message);
+    }
+    std::process::exit(result.exit_code());
+}
+
+/// Start a task and follow its progress until it finishes.
+/// Exits the process with the code of the job's `RunResult`.
+pub async fn run_task(runner: TaskRunner, task_name: String, json: bool) {
+    if !json {
+        match &runner {
+            TaskRunner::Local { .. } => println!("🚀 Starting task '{}' from command line...", task_name),
+            TaskRunner::Server(client) => println!("🚀 Starting task '{}' on {}...", task_name, client.base_url()),
+        }
+    }
+
+    let task = match runner.get_task(&task_name).await {
+        Ok(task) => task,
+        Err((result, message)) => abort_run(json, &task_name, None, result, &message),
+    };
+
+    if !json {
+        println!("📋 Task details:");
+        println!("   Name: {}", task.name);
+        println!("   Source: {}", task.source_path);
+        println!("   Remote: {}:{}", task.remote_name, task.remote_path);
+        println!("   Chunking: {}", if task.use_chunking { "enabled" } else { "disabled" });
+        if let Some(ref chunk_size) = task.chunk_size {
+            println!("   Chunk size: {}", chunk_size);
+        }
+        if let Some(ref bwlimit) = task.bwlimit {
+            println!("   Bandwidth limit: {}", bwlimit);
+        }
+        if let Some(ref retry) = task.retry {
+            println!("   Retries: up to {} attempts on rclone exit codes {:?}", retry.max_attempts, retry.exit_codes);
+        }
+        println!();
+    }
+
+    let job_response = runner.start_task(&task_name).await;
+    let job_id = match job_response.data {
+        Some(id) => id,
+        None => abort_run(
+            json,
+            &task_name,
+            None,
+            RunResult::Fatal,
+            &format!("Failed to start sync job: {}", job_response.error.unwrap_or_default()),
+        ),
+    };
+
+    if json {
+        print_json(&RunEvent::Started {
+            task: &task_name,
+            job_id: &job_id,
+            source: &task.source_path,
+            remote: format!("{}:{}", task.remote_name, task.remote_path),
+        });
+    } else {
+        println!("✅ Sync job started with ID: {}", job_id);
+        println!("📊 Monitoring progress...");
+        println!();
+    }
+
+    // Monitor progress
+    let mut failures = 0;
+    let progress = loop {
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+
+        let progress_response = runner.get_sync_progress(&job_id).await;
+        let Some(progress) = progress_response.data else {
+            failures += 1;
+            if failures >= MAX_PROGRESS_FAILURES {
+                abort_run(
+                    json,
+                    &task_name,
+                    Some(&job_id),
+                    RunResult::Fatal,
+                    &format!("Lost track of job {}: {}", job_id, progress_response.error.unwrap_or_default()),
+                );
+            }
+            continue;
+        };
+        failures = 0;
+
+        if handlers::sync::is_finished(&progress.status) {
+            break progress;
+        }
+
+        if json {
+            print_json(&RunEvent::Progress {
+                job_id: &job_id,
+                status: &progress.status,
+                progress: progress.progress,
+                transferred: progress.transferred,
+                total: progress.total,
+            });
+        } else {
+            println!("📈 Progress: {:.1}% | Status: {} | Transferred: {} / {}",
+                progress.progress,
+                progress.status,
+                format_bytes(progress.transferred),
+                format_bytes(progress.total)
+            );
+        }
+    };
+
+    let result = RunResult::from_progress(&progress);
+
+    if json {
+        print_json(&RunEvent::Finished {
+            task: &task_name,
+            job_id: Some(&job_id),
+            result,
+            exit_code: result.exit_code(),
+            status: &progress.status,
+            rclone_exit_code: progress.exit_code,
+            transferred: progress.transferred,
+            total: progress.total,
+            duration_secs: progress.end_time.map(|end| end - progress.start_time),
+        });
+    } else {
+        println!();
+        match result {
+            RunResult::Success => println!("✅ Task '{}' completed successfully!", task_name),
+            RunResult::Cancelled => eprintln!("🛑 Task '{}' was cancelled", task_name),
+            RunResult::PartialErrors => eprintln!(
+                "⚠️  Task '{}' finished with errors, some files were not transferred (rclone exit code {})",
+                task_name,
+                progress.exit_code.unwrap_or_default()
+            ),
+            _ => match progress.exit_code {
+                Some(code) => eprintln!("❌ Task '{}' failed: {} (rclone exit code {})", task_name, progress.status, code),
+                None => eprintln!("❌ Task '{}' failed: {}", task_name, progress.status),
+            },
+        }
+    }
+
+    // Notifications of in-process jobs are sent by this process
+    if matches!(runner, TaskRunner::Local { .. }) {
+        notifications::wait_pending().await;
+    }
+
+    std::process::exit(result.exit_code());
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes == 0 { return "0 B".to_string(); }
+
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let base = 1024_f64;
+    let log = (bytes as f64).log(base).floor() as usize;
+    let unit_index = log.min(UNITS.len() - 1);
+    let value = bytes as f64 / base.powi(unit_index as i32);
+
+    if unit_index >= 3 {
+        format!("{:.1} {}", value, UNITS[unit_index])
+    } else {
+        format!("{:.0} {}", value, UNITS[unit_index])
+    }
+}
@@ -0,0 +1,54 @@
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use crate::models::{ApiResponse, StartTaskRequest, SyncProgress, Task};
+
+/// Minimal client for the REST API of a running rclone-gui server
+pub struct ApiClient {
+    base_url: String,
+    http: Client,
+}
+
+impl ApiClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http: Client::new(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub async fn get_tasks(&self) -> ApiResponse<Vec<Task>> {
+        self.send(self.http.get(self.url("/api/tasks"))).await
+    }
+
+    pub async fn start_task(&self, task_name: &str) -> ApiResponse<String> {
+        let request = StartTaskRequest { task_name: task_name.to_string() };
+        self.send(self.http.post(self.url("/api/tasks/start")).json(&request)).await
+    }
+
+    pub async fn get_sync_progress(&self, job_id: &str) -> ApiResponse<SyncProgress> {
+        self.send(self.http.get(self.url(&format!("/api/sync/{}", job_id)))).await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Send a request and decode the `ApiResponse` envelope.
+    /// Transport and decoding failures are reported as error responses.
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> ApiResponse<T> {
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => return ApiResponse::error(&format!("Could not reach server {}: {}", self.base_url, e)),
+        };
+
+        let status = response.status();
+        match response.json::<ApiResponse<T>>().await {
+            Ok(body) => body,
+            Err(e) => ApiResponse::error(&format!("Unexpected response from server ({}): {}", status, e)),
+        }
+    }
+}
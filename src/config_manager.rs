@@ -1,21 +1,57 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use crate::models::{RcloneConfig, ConfigRequest};
-use std::path::Path;
-use configparser::ini::Ini;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+use sha2::{Digest, Sha256};
+use tokio::sync::{Mutex, MutexGuard, RwLock};
+use tracing::{info, warn};
+use crate::models::{RcloneConfig, ConfigRequest, ConfigBackup, ConfigSource, ConfigSyncState, ConfigSyncStatus, RemoteDiff};
+use std::path::PathBuf;
+use crate::ini::IniDocument;
+use crate::settings::Settings;
 use tokio::process::Command;
 
+const BACKUP_PREFIX: &str = "rclone.conf.";
+const BACKUP_SUFFIX: &str = ".bak";
+/// Number of previous config versions kept in the backup directory
+const MAX_CONFIG_BACKUPS: usize = 20;
+/// How often rclone.conf is checked for external edits in memory mode
+const FILE_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
 pub struct ConfigManager {
     memory_configs: Arc<RwLock<HashMap<String, RcloneConfig>>>,
     use_memory_only: bool,
+    config_path: PathBuf,
+    backup_dir: PathBuf,
+    rclone_binary: String,
+    /// Serializes every load → modify → write cycle on rclone.conf within this process,
+    /// `ConfigFileLock` across processes
+    file_lock: Mutex<()>,
+    /// rclone.conf as last loaded into or persisted from memory (the common base of a three-way diff)
+    disk_snapshot: RwLock<DiskSnapshot>,
+    conflict_reported: AtomicBool,
+}
+
+/// Content of rclone.conf at a point in time
+#[derive(Debug, Default, Clone)]
+struct DiskSnapshot {
+    hash: Option<String>,
+    modified: Option<SystemTime>,
+    configs: HashMap<String, RcloneConfig>,
 }
 
 impl ConfigManager {
-    pub fn new(use_memory_only: bool) -> Self {
+    pub fn new(use_memory_only: bool, settings: &Settings) -> Self {
         Self {
             memory_configs: Arc::new(RwLock::new(HashMap::new())),
             use_memory_only,
+            config_path: settings.rclone_config.clone(),
+            backup_dir: settings.config_backup_dir(),
+            rclone_binary: settings.rclone_binary.clone(),
+            file_lock: Mutex::new(()),
+            disk_snapshot: RwLock::new(DiskSnapshot::default()),
+            conflict_reported: AtomicBool::new(false),
         }
     }
 
@@ -45,19 +81,30 @@ impl ConfigManager {
             config_type: config_request.config_type.clone(),
             url: config_request.url.clone(),
             username: config_request.username.clone(),
-            password: obscured_password.clone(),
+            password: obscured_password,
             additional_fields: config_request.additional_fields.clone().unwrap_or_default(),
         };
 
+        self.store_config(config).await
+    }
+
+    /// Store a config whose password is already obscured (e.g. taken from an
+    /// existing rclone.conf or an imported bundle)
+    pub async fn store_config(&self, config: RcloneConfig) -> anyhow::Result<()> {
         if self.use_memory_only {
             let mut configs = self.memory_configs.write().await;
             configs.insert(config.name.clone(), config);
             Ok(())
         } else {
-            // Create a modified request with obscured password for file saving
-            let mut modified_request = config_request.clone();
-            modified_request.password = obscured_password;
-            self.save_to_file(&modified_request).await
+            let request = ConfigRequest {
+                name: config.name,
+                config_type: config.config_type,
+                url: config.url,
+                username: config.username,
+                password: config.password,
+                additional_fields: Some(config.additional_fields),
+            };
+            self.save_to_file(&request).await
         }
     }
 
@@ -76,111 +123,277 @@ impl ConfigManager {
             return Ok(());
         }
 
+        let _guard = self.lock_file().await?;
         let configs = self.memory_configs.read().await;
-        let config_path = "data/cfg/rclone.conf";
-        
-        // Ensure directory exists
-        if let Some(parent) = Path::new(config_path).parent() {
-            std::fs::create_dir_all(parent)?;
+
+        // Never overwrite edits made outside of this process
+        let current = self.read_disk_snapshot()?;
+        if current.hash != self.disk_snapshot.read().await.hash {
+            return Err(anyhow::anyhow!(
+                "rclone.conf was modified outside of rclone-gui, review and resolve the changes first"
+            ));
         }
-        
-        let mut conf = Ini::new();
-
-        for (_, config) in configs.iter() {
-            // Handle WebDAV subtypes and set appropriate type and vendor
-            let (actual_type, vendor) = match config.config_type.as_str() {
-                "webdav-nextcloud" => ("webdav", Some("nextcloud")),
-                "webdav-owncloud" => ("webdav", Some("owncloud")),
-                "webdav-sharepoint" => ("webdav", Some("sharepoint")),
-                "webdav-fastmail" => ("webdav", Some("fastmail")),
-                "webdav-other" => ("webdav", Some("other")),
-                _ => (config.config_type.as_str(), None),
-            };
 
-            conf.set(&config.name, "type", Some(actual_type.to_string()));
+        let mut document = IniDocument::load(&self.config_path)?;
 
-            if let Some(url) = &config.url {
-                conf.set(&config.name, "url", Some(url.clone()));
+        // Drop remotes that were deleted in memory
+        for section_name in document.section_names() {
+            if !configs.contains_key(&section_name) {
+                document.remove_section(&section_name);
             }
+        }
 
-            if let Some(username) = &config.username {
-                conf.set(&config.name, "user", Some(username.clone()));
-            }
+        let mut names: Vec<&String> = configs.keys().collect();
+        names.sort();
+
+        for name in names {
+            let config = &configs[name];
+            // Note: Passwords in memory configs should already be obscured
+            // when they were saved initially
+            let entries = Self::config_entries(
+                &config.config_type,
+                config.url.as_deref(),
+                config.username.as_deref(),
+                config.password.as_deref(),
+                &config.additional_fields,
+            );
+            document.set_section(&config.name, &entries);
+        }
+
+        self.write_config_file(&document.to_string())?;
+        *self.disk_snapshot.write().await = self.read_disk_snapshot()?;
+        Ok(())
+    }
+
+    /// Lock rclone.conf against writes of this and other processes, e.g. a CLI command next to a running server
+    async fn lock_file(&self) -> anyhow::Result<(MutexGuard<'_, ()>, ConfigFileLock)> {
+        let guard = self.file_lock.lock().await;
+        let lock_path = self.config_path.with_extension("conf.lock");
+        let file_lock = tokio::task::spawn_blocking(move || ConfigFileLock::acquire(&lock_path))
+            .await?
+            .map_err(|e| anyhow::anyhow!("Failed to lock config: {}", e))?;
+        Ok((guard, file_lock))
+    }
+
+    pub async fn load_from_file_to_memory(&self) -> anyhow::Result<()> {
+        let _guard = self.lock_file().await?;
+        let snapshot = self.read_disk_snapshot()?;
+        let mut memory_configs = self.memory_configs.write().await;
+        
+        for config in snapshot.configs.values() {
+            memory_configs.insert(config.name.clone(), config.clone());
+        }
+        *self.disk_snapshot.write().await = snapshot;
+        
+        Ok(())
+    }
 
-            if let Some(password) = &config.password {
-                if !password.is_empty() {
-                    // Note: Passwords in memory configs should already be obscured
-                    // when they were saved initially
-                    conf.set(&config.name, "pass", Some(password.clone()));
+    /// Periodically check rclone.conf for edits made outside of this process (memory mode only)
+    pub fn spawn_file_watcher(self: &Arc<Self>) {
+        if !self.use_memory_only {
+            return;
+        }
+
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(FILE_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = manager.check_for_external_changes().await {
+                    warn!("Could not check rclone.conf for external changes: {}", e);
                 }
             }
+        });
+    }
 
-            // Set vendor for WebDAV configurations
-            if let Some(vendor_value) = vendor {
-                conf.set(&config.name, "vendor", Some(vendor_value.to_string()));
+    /// Reload memory if only the file changed; flag a conflict if both memory and file changed
+    pub async fn check_for_external_changes(&self) -> anyhow::Result<ConfigSyncState> {
+        let _guard = self.lock_file().await?;
+
+        let modified = std::fs::metadata(&self.config_path).and_then(|m| m.modified()).ok();
+        {
+            let snapshot = self.disk_snapshot.read().await;
+            if modified == snapshot.modified {
+                drop(snapshot);
+                return Ok(self.sync_state(None).await);
             }
+        }
 
-            for (key, value) in &config.additional_fields {
-                conf.set(&config.name, key, Some(value.clone()));
+        let current = self.read_disk_snapshot()?;
+        let mut snapshot = self.disk_snapshot.write().await;
+
+        if current.hash == snapshot.hash {
+            // Touched but unchanged
+            snapshot.modified = current.modified;
+            drop(snapshot);
+            return Ok(self.sync_state(None).await);
+        }
+
+        let mut memory_configs = self.memory_configs.write().await;
+        if *memory_configs == snapshot.configs {
+            info!("🔄 rclone.conf changed on disk, reloading {} remotes into memory", current.configs.len());
+            *memory_configs = current.configs.clone();
+            *snapshot = current;
+            self.conflict_reported.store(false, Ordering::Relaxed);
+            return Ok(ConfigSyncState::InSync);
+        }
+
+        if !self.conflict_reported.swap(true, Ordering::Relaxed) {
+            warn!("⚠️  rclone.conf changed on disk while memory has unsaved changes - resolve the conflict before persisting");
+        }
+        Ok(ConfigSyncState::Conflict)
+    }
+
+    /// Three-way comparison of the base snapshot, the in-memory configs and the file on disk
+    pub async fn sync_status(&self) -> anyhow::Result<ConfigSyncStatus> {
+        // Without memory mode every change goes straight to the file
+        if !self.use_memory_only {
+            return Ok(ConfigSyncStatus {
+                state: ConfigSyncState::InSync,
+                remotes: Vec::new(),
+            });
+        }
+
+        let current = self.read_disk_snapshot()?;
+        let snapshot = self.disk_snapshot.read().await.clone();
+        let memory = self.memory_configs.read().await.clone();
+
+        let names: BTreeSet<&String> = snapshot
+            .configs
+            .keys()
+            .chain(memory.keys())
+            .chain(current.configs.keys())
+            .collect();
+
+        let remotes = names
+            .into_iter()
+            .filter_map(|name| {
+                let base = snapshot.configs.get(name);
+                let memory = memory.get(name);
+                let disk = current.configs.get(name);
+                let memory_changed = memory != base;
+                let disk_changed = disk != base;
+
+                (memory_changed || disk_changed).then(|| RemoteDiff {
+                    name: name.clone(),
+                    base: base.cloned(),
+                    memory: memory.cloned(),
+                    disk: disk.cloned(),
+                    memory_changed,
+                    disk_changed,
+                })
+            })
+            .collect();
+
+        Ok(ConfigSyncStatus {
+            state: self.sync_state(Some(&current)).await,
+            remotes,
+        })
+    }
+
+    /// Merge the file on disk into memory. Remotes changed on only one side take that side;
+    /// remotes changed on both sides use the given choice (or `default`).
+    pub async fn resolve_conflict(
+        &self,
+        choices: &HashMap<String, ConfigSource>,
+        default: ConfigSource,
+    ) -> anyhow::Result<ConfigSyncStatus> {
+        if !self.use_memory_only {
+            return Err(anyhow::anyhow!("Conflict resolution is only available in memory mode"));
+        }
+
+        {
+            let _guard = self.lock_file().await?;
+            let current = self.read_disk_snapshot()?;
+            let mut snapshot = self.disk_snapshot.write().await;
+            let mut memory = self.memory_configs.write().await;
+
+            let names: BTreeSet<String> = snapshot
+                .configs
+                .keys()
+                .chain(memory.keys())
+                .chain(current.configs.keys())
+                .cloned()
+                .collect();
+
+            for name in names {
+                let base = snapshot.configs.get(&name);
+                let disk = current.configs.get(&name);
+                let memory_changed = memory.get(&name) != base;
+                let disk_changed = disk != base;
+
+                let source = match choices.get(&name) {
+                    Some(choice) => *choice,
+                    None if disk_changed && !memory_changed => ConfigSource::Disk,
+                    None if disk_changed => default,
+                    None => ConfigSource::Memory,
+                };
+
+                if source == ConfigSource::Disk {
+                    match disk {
+                        Some(config) => memory.insert(name, config.clone()),
+                        None => memory.remove(&name),
+                    };
+                }
             }
+
+            *snapshot = current;
+            self.conflict_reported.store(false, Ordering::Relaxed);
         }
 
-        conf.write(config_path).map_err(|e| anyhow::anyhow!("Failed to write config: {}", e))?;
-        Ok(())
+        self.sync_status().await
     }
 
-    pub async fn load_from_file_to_memory(&self) -> anyhow::Result<()> {
-        let configs = self.load_from_file().await?;
-        let mut memory_configs = self.memory_configs.write().await;
-        
-        for config in configs {
-            memory_configs.insert(config.name.clone(), config);
+    async fn sync_state(&self, current: Option<&DiskSnapshot>) -> ConfigSyncState {
+        let snapshot = self.disk_snapshot.read().await;
+        let memory_changed = *self.memory_configs.read().await != snapshot.configs;
+        let disk_changed = match current {
+            Some(current) => current.hash != snapshot.hash,
+            None => self.conflict_reported.load(Ordering::Relaxed),
+        };
+
+        match (memory_changed, disk_changed) {
+            (false, false) => ConfigSyncState::InSync,
+            (true, false) => ConfigSyncState::MemoryChanged,
+            (false, true) => ConfigSyncState::DiskChanged,
+            (true, true) => ConfigSyncState::Conflict,
         }
-        
-        Ok(())
     }
 
     async fn load_from_file(&self) -> anyhow::Result<Vec<RcloneConfig>> {
-        let config_path = "data/cfg/rclone.conf";
-        
-        if !Path::new(config_path).exists() {
-            return Ok(Vec::new());
-        }
+        let document = IniDocument::load(&self.config_path)?;
+        Ok(Self::configs_from_document(&document))
+    }
 
-        let mut conf = Ini::new();
-        conf.load(config_path).map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+    fn configs_from_document(document: &IniDocument) -> Vec<RcloneConfig> {
         let mut configs = Vec::new();
 
-        for section_name in conf.sections() {
-                let rclone_type = conf.get(&section_name, "type").unwrap_or("".to_string());
-                let vendor = conf.get(&section_name, "vendor");
-                
-                let mut config = RcloneConfig {
-                    name: section_name.to_string(),
-                    config_type: Self::get_ui_config_type(&rclone_type, vendor.as_deref()),
-                    url: conf.get(&section_name, "url"),
-                    username: conf.get(&section_name, "user"),
-                    password: conf.get(&section_name, "pass"),
-                    additional_fields: HashMap::new(),
-                };
+        for section_name in document.section_names() {
+            let rclone_type = document.get(&section_name, "type").unwrap_or_default();
+            let vendor = document.get(&section_name, "vendor");
+
+            let mut config = RcloneConfig {
+                name: section_name.to_string(),
+                config_type: Self::get_ui_config_type(&rclone_type, vendor.as_deref()),
+                url: document.get(&section_name, "url"),
+                username: document.get(&section_name, "user"),
+                password: document.get(&section_name, "pass"),
+                additional_fields: HashMap::new(),
+            };
+
+            // Note: Passwords are loaded as-is from the config file
+            // New passwords will be automatically obscured when saved
 
-                // Note: Passwords are loaded as-is from the config file
-                // New passwords will be automatically obscured when saved
-
-                if let Some(section_map) = conf.get_map_ref().get(&section_name) {
-                    for (key, value) in section_map.iter() {
-                        if !matches!(key.as_str(), "type" | "url" | "user" | "pass" | "vendor") {
-                            if let Some(value) = value {
-                                config.additional_fields.insert(key.to_string(), value.to_string());
-                            }
-                        }
-                    }
+            for (key, value) in document.entries(&section_name).unwrap_or_default() {
+                if !matches!(key.as_str(), "type" | "url" | "user" | "pass" | "vendor") {
+                    config.additional_fields.insert(key, value);
                 }
+            }
 
-                configs.push(config);
+            configs.push(config);
         }
 
-        Ok(configs)
+        configs
     }
 
     /// Helper function to map rclone type and vendor back to UI subtype
@@ -196,80 +409,193 @@ impl ConfigManager {
         }
     }
 
-    async fn save_to_file(&self, config_request: &ConfigRequest) -> anyhow::Result<()> {
-        let config_path = "data/cfg/rclone.conf";
-        
-        // Ensure directory exists
-        if let Some(parent) = Path::new(config_path).parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        
-        let mut conf = Ini::new();
-        
-        if Path::new(config_path).exists() {
-            conf.load(config_path).map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
-        }
-
+    /// Build the rclone.conf entries of a remote in rclone's usual key order
+    fn config_entries(
+        config_type: &str,
+        url: Option<&str>,
+        username: Option<&str>,
+        password: Option<&str>,
+        additional_fields: &HashMap<String, String>,
+    ) -> Vec<(String, String)> {
         // Handle WebDAV subtypes and set appropriate type and vendor
-        let (actual_type, vendor) = match config_request.config_type.as_str() {
+        let (actual_type, vendor) = match config_type {
             "webdav-nextcloud" => ("webdav", Some("nextcloud")),
             "webdav-owncloud" => ("webdav", Some("owncloud")),
             "webdav-sharepoint" => ("webdav", Some("sharepoint")),
             "webdav-fastmail" => ("webdav", Some("fastmail")),
             "webdav-other" => ("webdav", Some("other")),
-            _ => (config_request.config_type.as_str(), None),
+            _ => (config_type, None),
         };
 
-        conf.set(&config_request.name, "type", Some(actual_type.to_string()));
+        let mut entries = vec![("type".to_string(), actual_type.to_string())];
 
-        if let Some(url) = &config_request.url {
-            conf.set(&config_request.name, "url", Some(url.clone()));
+        if let Some(url) = url {
+            entries.push(("url".to_string(), url.to_string()));
         }
 
-        if let Some(username) = &config_request.username {
-            conf.set(&config_request.name, "user", Some(username.clone()));
+        // Set vendor for WebDAV configurations
+        if let Some(vendor_value) = vendor {
+            entries.push(("vendor".to_string(), vendor_value.to_string()));
         }
 
-        if let Some(password) = &config_request.password {
-            if !password.is_empty() {
-                // Password should already be obscured when passed to this method
-                conf.set(&config_request.name, "pass", Some(password.clone()));
-            }
+        if let Some(username) = username {
+            entries.push(("user".to_string(), username.to_string()));
         }
 
-        // Set vendor for WebDAV configurations
-        if let Some(vendor_value) = vendor {
-            conf.set(&config_request.name, "vendor", Some(vendor_value.to_string()));
+        if let Some(password) = password.filter(|p| !p.is_empty()) {
+            entries.push(("pass".to_string(), password.to_string()));
         }
 
-        if let Some(additional_fields) = &config_request.additional_fields {
-            for (key, value) in additional_fields {
-                conf.set(&config_request.name, key, Some(value.clone()));
-            }
+        let mut additional: Vec<(&String, &String)> = additional_fields.iter().collect();
+        additional.sort();
+        for (key, value) in additional {
+            entries.push((key.clone(), value.clone()));
         }
 
-        conf.write(config_path).map_err(|e| anyhow::anyhow!("Failed to write config: {}", e))?;
-        Ok(())
+        entries
+    }
+
+    async fn save_to_file(&self, config_request: &ConfigRequest) -> anyhow::Result<()> {
+        let _guard = self.lock_file().await?;
+        let mut document = IniDocument::load(&self.config_path)?;
+
+        // Password should already be obscured when passed to this method.
+        // Keys not mentioned in the request (e.g. OAuth tokens) are left untouched.
+        let entries = Self::config_entries(
+            &config_request.config_type,
+            config_request.url.as_deref(),
+            config_request.username.as_deref(),
+            config_request.password.as_deref(),
+            &config_request.additional_fields.clone().unwrap_or_default(),
+        );
+        for (key, value) in &entries {
+            document.set(&config_request.name, key, value);
+        }
+
+        self.write_config_file(&document.to_string())
     }
 
     async fn delete_from_file(&self, name: &str) -> anyhow::Result<()> {
-        let config_path = "data/cfg/rclone.conf";
-        
-        if !Path::new(config_path).exists() {
+        let _guard = self.lock_file().await?;
+        if !self.config_path.exists() {
             return Ok(());
         }
 
-        let mut conf = Ini::new();
-        conf.load(config_path).map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
-        conf.remove_section(name);
-        conf.write(config_path).map_err(|e| anyhow::anyhow!("Failed to write config: {}", e))?;
+        let mut document = IniDocument::load(&self.config_path)?;
+        if document.remove_section(name) {
+            self.write_config_file(&document.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// List the backups of previous rclone.conf versions, newest first
+    pub async fn list_backups(&self) -> anyhow::Result<Vec<ConfigBackup>> {
+        let mut backups = Vec::new();
+
+        let entries = match std::fs::read_dir(&self.backup_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(backups),
+            Err(e) => return Err(e.into()),
+        };
+
+        for entry in entries {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let Some(id) = backup_id_from_file_name(&file_name) else {
+                continue;
+            };
+            let timestamp = id.split_once('-').map_or(id, |(timestamp, _)| timestamp);
+            let Ok(created_at) = chrono::NaiveDateTime::parse_from_str(timestamp, "%Y%m%dT%H%M%S%.3fZ") else {
+                continue;
+            };
+
+            backups.push(ConfigBackup {
+                id: id.to_string(),
+                created_at: created_at.and_utc(),
+                size: entry.metadata()?.len(),
+            });
+        }
+
+        backups.sort_by(|a, b| backup_order(&b.id).cmp(&backup_order(&a.id)));
+        Ok(backups)
+    }
+
+    /// Restore a previous rclone.conf version; the current file is backed up first
+    pub async fn restore_backup(&self, id: &str) -> anyhow::Result<()> {
+        let _guard = self.lock_file().await?;
+
+        if !self.list_backups().await?.iter().any(|b| b.id == id) {
+            return Err(anyhow::anyhow!("Backup not found"));
+        }
+
+        let backup_path = self.backup_dir.join(format!("{}{}{}", BACKUP_PREFIX, id, BACKUP_SUFFIX));
+        let content = std::fs::read_to_string(backup_path)?;
+        self.write_config_file(&content)?;
+
+        // Keep the in-memory store in sync with the restored file
+        if self.use_memory_only {
+            let snapshot = self.read_disk_snapshot()?;
+            *self.memory_configs.write().await = snapshot.configs.clone();
+            *self.disk_snapshot.write().await = snapshot;
+        }
+
         Ok(())
     }
 
+    /// Check that a remote is reachable by listing its top level
+    pub async fn test_remote(&self, name: &str) -> anyhow::Result<String> {
+        let configs = self.load_configs().await?;
+        let config = configs
+            .into_iter()
+            .find(|c| c.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Configuration not found"))?;
+
+        // Unsaved memory configs are not in rclone.conf yet, so hand rclone a temporary config
+        let temp_config = if self.use_memory_only {
+            let mut document = IniDocument::default();
+            let entries = Self::config_entries(
+                &config.config_type,
+                config.url.as_deref(),
+                config.username.as_deref(),
+                config.password.as_deref(),
+                &config.additional_fields,
+            );
+            document.set_section(&config.name, &entries);
+
+            let path = std::env::temp_dir().join(format!("rclone-gui-test-{}.conf", uuid::Uuid::new_v4()));
+            std::fs::write(&path, document.to_string())?;
+            Some(path)
+        } else {
+            None
+        };
+        let config_path = temp_config.as_ref().unwrap_or(&self.config_path);
+
+        let output = Command::new(&self.rclone_binary)
+            .arg("lsjson")
+            .arg("--config")
+            .arg(config_path)
+            .args(["--max-depth", "1", "--dirs-only", &format!("{}:", name)])
+            .output()
+            .await;
+
+        if let Some(path) = &temp_config {
+            let _ = std::fs::remove_file(path);
+        }
+
+        let output = output?;
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow::anyhow!("Remote test failed: {}", error.trim()));
+        }
+
+        let entries: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap_or_default();
+        Ok(format!("Remote '{}' is reachable ({} top-level directories)", name, entries.len()))
+    }
+
     /// Obscure password using rclone obscure command
     async fn obscure_password(&self, password: &str) -> anyhow::Result<String> {
-        let output = Command::new("rclone")
-            .args(&["obscure", password])
+        let output = Command::new(&self.rclone_binary)
+            .args(["obscure", password])
             .output()
             .await?;
 
@@ -284,8 +610,8 @@ impl ConfigManager {
 
     /// Reveal password using rclone reveal command (for display purposes)
     pub async fn reveal_password(&self, obscured_password: &str) -> anyhow::Result<String> {
-        let output = Command::new("rclone")
-            .args(&["reveal", obscured_password])
+        let output = Command::new(&self.rclone_binary)
+            .args(["reveal", obscured_password])
             .output()
             .await?;
 
@@ -298,4 +624,171 @@ impl ConfigManager {
         Ok(revealed.trim().to_string())
     }
 
-}
\ No newline at end of file
+    /// Read rclone.conf together with its hash and modification time
+    fn read_disk_snapshot(&self) -> anyhow::Result<DiskSnapshot> {
+        let content = match std::fs::read_to_string(&self.config_path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(DiskSnapshot::default()),
+            Err(e) => return Err(anyhow::anyhow!("Failed to load config: {}", e)),
+        };
+
+        let configs = Self::configs_from_document(&IniDocument::parse(&content))
+            .into_iter()
+            .map(|config| (config.name.clone(), config))
+            .collect();
+
+        Ok(DiskSnapshot {
+            hash: Some(format!("{:x}", Sha256::digest(content.as_bytes()))),
+            modified: std::fs::metadata(&self.config_path).and_then(|m| m.modified()).ok(),
+            configs,
+        })
+    }
+
+    /// Atomically replace rclone.conf with the given content.
+    /// The previous version is kept as a timestamped backup and the new content is
+    /// written to a temp file and renamed into place, so a crash never leaves a half-written config.
+    fn write_config_file(&self, content: &str) -> anyhow::Result<()> {
+        let config_path = self.config_path.as_path();
+
+        if let Some(parent) = config_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        if config_path.exists() {
+            let current = std::fs::read_to_string(config_path)?;
+            if current == content {
+                return Ok(());
+            }
+            self.backup_config_file(&current)?;
+        }
+
+        let temp_path = config_path.with_extension("conf.tmp");
+        {
+            let mut file = std::fs::File::create(&temp_path)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+        }
+        std::fs::rename(&temp_path, config_path)
+            .map_err(|e| anyhow::anyhow!("Failed to write config: {}", e))?;
+
+        // The rename is only durable once the directory entry is on disk
+        #[cfg(unix)]
+        if let Some(parent) = config_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::File::open(parent)?.sync_all()?;
+        }
+
+        Ok(())
+    }
+
+    /// Save the current config as a new backup and drop the oldest ones beyond the limit
+    fn backup_config_file(&self, content: &str) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.backup_dir)?;
+
+        // Backups of the same millisecond get a counter instead of replacing each other
+        let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ").to_string();
+        let mut id = timestamp.clone();
+        let mut counter = 0;
+        let mut file = loop {
+            let backup_path = self.backup_dir.join(format!("{}{}{}", BACKUP_PREFIX, id, BACKUP_SUFFIX));
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(backup_path) {
+                Ok(file) => break file,
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    counter += 1;
+                    id = format!("{}-{}", timestamp, counter);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+        file.write_all(content.as_bytes())?;
+
+        let mut backups: Vec<String> = std::fs::read_dir(&self.backup_dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| backup_id_from_file_name(name).is_some())
+            .collect();
+        backups.sort_by(|a, b| backup_id_from_file_name(a).map(backup_order).cmp(&backup_id_from_file_name(b).map(backup_order)));
+
+        if backups.len() > MAX_CONFIG_BACKUPS {
+            for name in &backups[..backups.len() - MAX_CONFIG_BACKUPS] {
+                if let Err(e) = std::fs::remove_file(self.backup_dir.join(name)) {
+                    tracing::warn!("Could not remove old config backup {}: {}", name, e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Exclusive advisory lock (`flock`) on a file next to rclone.conf, released on drop
+struct ConfigFileLock {
+    _file: std::fs::File,
+}
+
+impl ConfigFileLock {
+    /// Wait until no other process holds the lock
+    fn acquire(path: &std::path::Path) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::fs::OpenOptions::new().create(true).truncate(false).write(true).open(path)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::AsRawFd;
+            // SAFETY: flock() only operates on the file descriptor owned by `file`
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        Ok(Self { _file: file })
+    }
+}
+
+fn backup_id_from_file_name(file_name: &str) -> Option<&str> {
+    file_name
+        .strip_prefix(BACKUP_PREFIX)
+        .and_then(|rest| rest.strip_suffix(BACKUP_SUFFIX))
+}
+
+/// Chronological sort key of a backup id: its timestamp and the counter of backups
+/// of the same millisecond, so `…-10` comes after `…-2`
+fn backup_order(id: &str) -> (&str, u32) {
+    match id.split_once('-') {
+        Some((timestamp, counter)) => (timestamp, counter.parse().unwrap_or(0)),
+        None => (id, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backups_sort_by_timestamp_then_counter() {
+        let mut ids = vec![
+            "20260101T000000.001Z-10",
+            "20260101T000000.001Z",
+            "20251231T235959.999Z",
+            "20260101T000000.001Z-2",
+            "20260101T000000.002Z",
+        ];
+        ids.sort_by_key(|id| backup_order(id));
+
+        assert_eq!(ids, vec![
+            "20251231T235959.999Z",
+            "20260101T000000.001Z",
+            "20260101T000000.001Z-2",
+            "20260101T000000.001Z-10",
+            "20260101T000000.002Z",
+        ]);
+    }
+
+    #[test]
+    fn backup_ids_come_from_file_names() {
+        assert_eq!(
+            backup_id_from_file_name(&format!("{}20260101T000000.001Z-3{}", BACKUP_PREFIX, BACKUP_SUFFIX)),
+            Some("20260101T000000.001Z-3")
+        );
+        assert_eq!(backup_id_from_file_name("rclone.conf"), None);
+    }
+}
@@ -1,14 +1,19 @@
-use sqlx::{Pool, Sqlite, SqlitePool};
+use sqlx::{Pool, Sqlite, SqliteConnection, SqlitePool};
+use sqlx::sqlite::SqliteConnectOptions;
 use anyhow::Result;
-use crate::models::Task;
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use crate::models::{ChannelConfig, JobAttempt, JobError, JobRecord, NotificationChannel, NotifyOn, StatsGroupBy, StatsInterval, Task, TaskNotification};
+use crate::settings::Settings;
 use tracing::info;
 
-pub async fn init_database() -> Result<Pool<Sqlite>> {
+pub async fn init_database(settings: &Settings) -> Result<Pool<Sqlite>> {
     // Create data directory if it doesn't exist
-    tokio::fs::create_dir_all("data").await?;
+    tokio::fs::create_dir_all(&settings.data_dir).await?;
     
-    let database_url = "sqlite:data/tasks.db?mode=rwc";
-    let pool = SqlitePool::connect(database_url).await?;
+    let options = SqliteConnectOptions::from_str(&settings.database_url)?
+        .create_if_missing(true);
+    let pool = SqlitePool::connect_with(options).await?;
     
     // Create tasks table
     sqlx::query(r#"
@@ -20,20 +25,145 @@ pub async fn init_database() -> Result<Pool<Sqlite>> {
             remote_path TEXT NOT NULL,
             chunk_size TEXT,
             use_chunking BOOLEAN NOT NULL DEFAULT FALSE,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            bwlimit TEXT,
+            resume_interrupted BOOLEAN NOT NULL DEFAULT FALSE,
+            retry TEXT
+        )
+    "#)
+    .execute(&pool)
+    .await?;
+    add_column_if_missing(&pool, "tasks", "bwlimit", "TEXT").await?;
+    add_column_if_missing(&pool, "tasks", "resume_interrupted", "BOOLEAN NOT NULL DEFAULT FALSE").await?;
+    add_column_if_missing(&pool, "tasks", "retry", "TEXT").await?;
+    
+    // Create jobs table
+    sqlx::query(r#"
+        CREATE TABLE IF NOT EXISTS jobs (
+            id TEXT PRIMARY KEY,
+            task_name TEXT,
+            source_path TEXT NOT NULL,
+            remote_name TEXT NOT NULL,
+            remote_path TEXT NOT NULL,
+            status TEXT NOT NULL,
+            progress REAL NOT NULL DEFAULT 0,
+            transferred INTEGER NOT NULL DEFAULT 0,
+            total INTEGER NOT NULL DEFAULT 0,
+            pid INTEGER,
+            exit_code INTEGER,
+            start_time INTEGER NOT NULL,
+            end_time INTEGER,
+            files INTEGER NOT NULL DEFAULT 0,
+            rc_port INTEGER,
+            rc_password TEXT,
+            attempt INTEGER NOT NULL DEFAULT 1
+        )
+    "#)
+    .execute(&pool)
+    .await?;
+    add_column_if_missing(&pool, "jobs", "files", "INTEGER NOT NULL DEFAULT 0").await?;
+    add_column_if_missing(&pool, "jobs", "rc_port", "INTEGER").await?;
+    add_column_if_missing(&pool, "jobs", "rc_password", "TEXT").await?;
+    add_column_if_missing(&pool, "jobs", "attempt", "INTEGER NOT NULL DEFAULT 1").await?;
+    
+    // Create notification tables
+    sqlx::query(r#"
+        CREATE TABLE IF NOT EXISTS notification_channels (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            config TEXT NOT NULL,
+            enabled BOOLEAN NOT NULL DEFAULT TRUE,
             created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
         )
     "#)
     .execute(&pool)
     .await?;
+
+    sqlx::query(r#"
+        CREATE TABLE IF NOT EXISTS task_notifications (
+            task_id TEXT NOT NULL,
+            channel_id TEXT NOT NULL,
+            notify_on TEXT NOT NULL,
+            PRIMARY KEY (task_id, channel_id)
+        )
+    "#)
+    .execute(&pool)
+    .await?;
     
+    sqlx::query(r#"
+        CREATE TABLE IF NOT EXISTS job_errors (
+            job_id TEXT NOT NULL,
+            object TEXT NOT NULL,
+            message TEXT NOT NULL,
+            retries INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (job_id, object)
+        )
+    "#)
+    .execute(&pool)
+    .await?;
+
+    sqlx::query(r#"
+        CREATE TABLE IF NOT EXISTS job_attempts (
+            job_id TEXT NOT NULL,
+            attempt INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            exit_code INTEGER,
+            start_time INTEGER NOT NULL,
+            end_time INTEGER,
+            PRIMARY KEY (job_id, attempt)
+        )
+    "#)
+    .execute(&pool)
+    .await?;
+
+    // Daily sums of the finished jobs removed by the retention policy, kept for the transfer history,
+    // and the end time of the last completed one for the task metrics
+    sqlx::query(r#"
+        CREATE TABLE IF NOT EXISTS job_stats (
+            day INTEGER NOT NULL,
+            task_name TEXT,
+            remote_name TEXT NOT NULL,
+            jobs INTEGER NOT NULL,
+            succeeded INTEGER NOT NULL,
+            failed INTEGER NOT NULL,
+            bytes INTEGER NOT NULL,
+            files INTEGER NOT NULL,
+            duration_secs INTEGER NOT NULL,
+            timed_bytes INTEGER NOT NULL,
+            last_success INTEGER
+        )
+    "#)
+    .execute(&pool)
+    .await?;
+    add_column_if_missing(&pool, "job_stats", "last_success", "INTEGER").await?;
+
     info!("✅ Database initialized successfully");
     Ok(pool)
 }
 
+/// Add a column introduced after the table was first created
+async fn add_column_if_missing(pool: &Pool<Sqlite>, table: &str, column: &str, definition: &str) -> Result<()> {
+    let count: (i64,) = sqlx::query_as(r#"
+        SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?
+    "#)
+    .bind(table)
+    .bind(column)
+    .fetch_one(pool)
+    .await?;
+
+    if count.0 == 0 {
+        sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+            .execute(pool)
+            .await?;
+        info!("🔧 Added column {}.{}", table, column);
+    }
+    Ok(())
+}
+
 pub async fn create_task(pool: &Pool<Sqlite>, task: &Task) -> Result<()> {
     sqlx::query(r#"
-        INSERT INTO tasks (id, name, source_path, remote_name, remote_path, chunk_size, use_chunking, created_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        INSERT INTO tasks (id, name, source_path, remote_name, remote_path, chunk_size, use_chunking, bwlimit, resume_interrupted, retry, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
     "#)
     .bind(&task.id)
     .bind(&task.name)
@@ -42,6 +172,9 @@ pub async fn create_task(pool: &Pool<Sqlite>, task: &Task) -> Result<()> {
     .bind(&task.remote_path)
     .bind(&task.chunk_size)
     .bind(task.use_chunking)
+    .bind(&task.bwlimit)
+    .bind(task.resume_interrupted)
+    .bind(task.retry.as_ref().map(serde_json::to_string).transpose()?)
     .bind(task.created_at)
     .execute(pool)
     .await?;
@@ -51,7 +184,7 @@ pub async fn create_task(pool: &Pool<Sqlite>, task: &Task) -> Result<()> {
 
 pub async fn get_all_tasks(pool: &Pool<Sqlite>) -> Result<Vec<Task>> {
     let tasks = sqlx::query_as::<_, Task>(r#"
-        SELECT id, name, source_path, remote_name, remote_path, chunk_size, use_chunking, created_at
+        SELECT id, name, source_path, remote_name, remote_path, chunk_size, use_chunking, bwlimit, resume_interrupted, retry, created_at
         FROM tasks
         ORDER BY created_at DESC
     "#)
@@ -63,7 +196,7 @@ pub async fn get_all_tasks(pool: &Pool<Sqlite>) -> Result<Vec<Task>> {
 
 pub async fn get_task_by_name(pool: &Pool<Sqlite>, name: &str) -> Result<Option<Task>> {
     let task = sqlx::query_as::<_, Task>(r#"
-        SELECT id, name, source_path, remote_name, remote_path, chunk_size, use_chunking, created_at
+        SELECT id, name, source_path, remote_name, remote_path, chunk_size, use_chunking, bwlimit, resume_interrupted, retry, created_at
         FROM tasks
         WHERE name = ?
     "#)
@@ -81,6 +214,13 @@ pub async fn delete_task(pool: &Pool<Sqlite>, task_id: &str) -> Result<bool> {
     .bind(task_id)
     .execute(pool)
     .await?;
+
+    sqlx::query(r#"
+        DELETE FROM task_notifications WHERE task_id = ?
+    "#)
+    .bind(task_id)
+    .execute(pool)
+    .await?;
     
     Ok(result.rows_affected() > 0)
 }
@@ -94,4 +234,525 @@ pub async fn task_name_exists(pool: &Pool<Sqlite>, name: &str) -> Result<bool> {
     .await?;
     
     Ok(count.0 > 0)
-}
\ No newline at end of file
+}
+
+pub async fn update_task(pool: &Pool<Sqlite>, task: &Task) -> Result<bool> {
+    let result = sqlx::query(r#"
+        UPDATE tasks
+        SET name = ?, source_path = ?, remote_name = ?, remote_path = ?, chunk_size = ?, use_chunking = ?, bwlimit = ?, resume_interrupted = ?, retry = ?
+        WHERE id = ?
+    "#)
+    .bind(&task.name)
+    .bind(&task.source_path)
+    .bind(&task.remote_name)
+    .bind(&task.remote_path)
+    .bind(&task.chunk_size)
+    .bind(task.use_chunking)
+    .bind(&task.bwlimit)
+    .bind(task.resume_interrupted)
+    .bind(task.retry.as_ref().map(serde_json::to_string).transpose()?)
+    .bind(&task.id)
+    .execute(pool)
+    .await?;
+    
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn save_job(pool: &Pool<Sqlite>, job: &JobRecord) -> Result<()> {
+    sqlx::query(r#"
+        INSERT OR REPLACE INTO jobs (id, task_name, source_path, remote_name, remote_path, status, progress, transferred, total, pid, exit_code, start_time, end_time, files, rc_port, rc_password, attempt)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    "#)
+    .bind(&job.id)
+    .bind(&job.task_name)
+    .bind(&job.source_path)
+    .bind(&job.remote_name)
+    .bind(&job.remote_path)
+    .bind(&job.status)
+    .bind(job.progress)
+    .bind(job.transferred)
+    .bind(job.total)
+    .bind(job.pid)
+    .bind(job.exit_code)
+    .bind(job.start_time)
+    .bind(job.end_time)
+    .bind(job.files)
+    .bind(job.rc_port)
+    .bind(&job.rc_password)
+    .bind(job.attempt)
+    .execute(pool)
+    .await?;
+    
+    Ok(())
+}
+
+/// Update a job unless its cancellation was requested, possibly by another process.
+/// Returns false if the job is being cancelled and was left unchanged.
+pub async fn update_job_unless_cancelling(pool: &Pool<Sqlite>, job: &JobRecord) -> Result<bool> {
+    let result = sqlx::query(r#"
+        UPDATE jobs
+        SET task_name = ?, source_path = ?, remote_name = ?, remote_path = ?, status = ?, progress = ?, transferred = ?, total = ?,
+            pid = ?, exit_code = ?, start_time = ?, end_time = ?, files = ?, rc_port = ?, rc_password = ?, attempt = ?
+        WHERE id = ? AND status != 'Cancelling'
+    "#)
+    .bind(&job.task_name)
+    .bind(&job.source_path)
+    .bind(&job.remote_name)
+    .bind(&job.remote_path)
+    .bind(&job.status)
+    .bind(job.progress)
+    .bind(job.transferred)
+    .bind(job.total)
+    .bind(job.pid)
+    .bind(job.exit_code)
+    .bind(job.start_time)
+    .bind(job.end_time)
+    .bind(job.files)
+    .bind(job.rc_port)
+    .bind(&job.rc_password)
+    .bind(job.attempt)
+    .bind(&job.id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_all_jobs(pool: &Pool<Sqlite>) -> Result<Vec<JobRecord>> {
+    let jobs = sqlx::query_as::<_, JobRecord>(r#"
+        SELECT id, task_name, source_path, remote_name, remote_path, status, progress, transferred, total, pid, exit_code, start_time, end_time, files, rc_port, rc_password, attempt
+        FROM jobs
+        ORDER BY start_time DESC
+    "#)
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(jobs)
+}
+
+pub async fn get_job(pool: &Pool<Sqlite>, job_id: &str) -> Result<Option<JobRecord>> {
+    let job = sqlx::query_as::<_, JobRecord>(r#"
+        SELECT id, task_name, source_path, remote_name, remote_path, status, progress, transferred, total, pid, exit_code, start_time, end_time, files, rc_port, rc_password, attempt
+        FROM jobs
+        WHERE id = ?
+    "#)
+    .bind(job_id)
+    .fetch_optional(pool)
+    .await?;
+    
+    Ok(job)
+}
+
+pub async fn set_job_status(pool: &Pool<Sqlite>, job_id: &str, status: &str) -> Result<()> {
+    sqlx::query(r#"
+        UPDATE jobs SET status = ? WHERE id = ?
+    "#)
+    .bind(status)
+    .bind(job_id)
+    .execute(pool)
+    .await?;
+    
+    Ok(())
+}
+
+/// Change the status of a job only if it still has the `from` status, false otherwise
+pub async fn replace_job_status(pool: &Pool<Sqlite>, job_id: &str, from: &str, to: &str) -> Result<bool> {
+    let result = sqlx::query(r#"
+        UPDATE jobs SET status = ? WHERE id = ? AND status = ?
+    "#)
+    .bind(to)
+    .bind(job_id)
+    .bind(from)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_job(pool: &Pool<Sqlite>, job_id: &str) -> Result<bool> {
+    let mut transaction = pool.begin().await?;
+    let deleted = delete_job_records(&mut transaction, job_id).await?;
+    transaction.commit().await?;
+    Ok(deleted)
+}
+
+/// Delete a job with its failed objects and attempts
+async fn delete_job_records(connection: &mut SqliteConnection, job_id: &str) -> Result<bool> {
+    let result = sqlx::query(r#"
+        DELETE FROM jobs WHERE id = ?
+    "#)
+    .bind(job_id)
+    .execute(&mut *connection)
+    .await?;
+
+    sqlx::query(r#"
+        DELETE FROM job_errors WHERE job_id = ?
+    "#)
+    .bind(job_id)
+    .execute(&mut *connection)
+    .await?;
+
+    sqlx::query(r#"
+        DELETE FROM job_attempts WHERE job_id = ?
+    "#)
+    .bind(job_id)
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete a finished job after adding it to the daily sums of `job_stats`, so it stays in the
+/// transfer history. Both happen in one transaction, a job is never counted twice or lost.
+pub async fn roll_up_and_delete_job(pool: &Pool<Sqlite>, job_id: &str) -> Result<bool> {
+    let mut transaction = pool.begin().await?;
+
+    let updated = sqlx::query(r#"
+        UPDATE job_stats
+        SET jobs = job_stats.jobs + 1,
+            succeeded = job_stats.succeeded + (j.status = 'Completed'),
+            failed = job_stats.failed + (j.status LIKE 'Failed%' OR j.status LIKE 'Error%'),
+            bytes = job_stats.bytes + j.transferred,
+            files = job_stats.files + j.files,
+            duration_secs = job_stats.duration_secs + (j.end_time - j.start_time),
+            timed_bytes = job_stats.timed_bytes + (CASE WHEN j.end_time > j.start_time THEN j.transferred ELSE 0 END),
+            last_success = CASE WHEN j.status = 'Completed'
+                THEN MAX(COALESCE(job_stats.last_success, j.end_time), j.end_time)
+                ELSE job_stats.last_success END
+        FROM jobs AS j
+        WHERE j.id = ? AND j.end_time IS NOT NULL
+          AND job_stats.day = j.start_time - j.start_time % 86400
+          AND job_stats.task_name IS j.task_name
+          AND job_stats.remote_name = j.remote_name
+    "#)
+    .bind(job_id)
+    .execute(&mut *transaction)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        sqlx::query(r#"
+            INSERT INTO job_stats (day, task_name, remote_name, jobs, succeeded, failed, bytes, files, duration_secs, timed_bytes, last_success)
+            SELECT start_time - start_time % 86400, task_name, remote_name, 1,
+                   status = 'Completed',
+                   status LIKE 'Failed%' OR status LIKE 'Error%',
+                   transferred, files, end_time - start_time,
+                   CASE WHEN end_time > start_time THEN transferred ELSE 0 END,
+                   CASE WHEN status = 'Completed' THEN end_time END
+            FROM jobs
+            WHERE id = ? AND end_time IS NOT NULL
+        "#)
+        .bind(job_id)
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    let deleted = delete_job_records(&mut transaction, job_id).await?;
+    transaction.commit().await?;
+    Ok(deleted)
+}
+
+/// Replace the failed objects recorded for a job
+pub async fn save_job_errors(pool: &Pool<Sqlite>, job_id: &str, errors: &[JobError]) -> Result<()> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query(r#"
+        DELETE FROM job_errors WHERE job_id = ?
+    "#)
+    .bind(job_id)
+    .execute(&mut *transaction)
+    .await?;
+
+    for error in errors {
+        sqlx::query(r#"
+            INSERT INTO job_errors (job_id, object, message, retries)
+            VALUES (?, ?, ?, ?)
+        "#)
+        .bind(job_id)
+        .bind(&error.object)
+        .bind(&error.message)
+        .bind(error.retries)
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    transaction.commit().await?;
+    Ok(())
+}
+
+pub async fn get_job_errors(pool: &Pool<Sqlite>, job_id: &str) -> Result<Vec<JobError>> {
+    let errors = sqlx::query_as::<_, JobError>(r#"
+        SELECT object, message, retries
+        FROM job_errors
+        WHERE job_id = ?
+        ORDER BY object
+    "#)
+    .bind(job_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(errors)
+}
+
+pub async fn save_job_attempt(pool: &Pool<Sqlite>, job_id: &str, attempt: &JobAttempt) -> Result<()> {
+    sqlx::query(r#"
+        INSERT OR REPLACE INTO job_attempts (job_id, attempt, status, exit_code, start_time, end_time)
+        VALUES (?, ?, ?, ?, ?, ?)
+    "#)
+    .bind(job_id)
+    .bind(attempt.attempt)
+    .bind(&attempt.status)
+    .bind(attempt.exit_code)
+    .bind(attempt.start_time)
+    .bind(attempt.end_time)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_job_attempts(pool: &Pool<Sqlite>, job_id: &str) -> Result<Vec<JobAttempt>> {
+    let attempts = sqlx::query_as::<_, JobAttempt>(r#"
+        SELECT attempt, status, exit_code, start_time, end_time
+        FROM job_attempts
+        WHERE job_id = ?
+        ORDER BY attempt
+    "#)
+    .bind(job_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(attempts)
+}
+
+type ChannelRow = (String, String, String, bool, DateTime<Utc>);
+
+fn channel_from_row((id, name, config, enabled, created_at): ChannelRow) -> Result<NotificationChannel> {
+    let config: ChannelConfig = serde_json::from_str(&config)?;
+    Ok(NotificationChannel { id, name, config, enabled, created_at })
+}
+
+pub async fn create_channel(pool: &Pool<Sqlite>, channel: &NotificationChannel) -> Result<()> {
+    sqlx::query(r#"
+        INSERT INTO notification_channels (id, name, config, enabled, created_at)
+        VALUES (?, ?, ?, ?, ?)
+    "#)
+    .bind(&channel.id)
+    .bind(&channel.name)
+    .bind(serde_json::to_string(&channel.config)?)
+    .bind(channel.enabled)
+    .bind(channel.created_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_all_channels(pool: &Pool<Sqlite>) -> Result<Vec<NotificationChannel>> {
+    let rows = sqlx::query_as::<_, ChannelRow>(r#"
+        SELECT id, name, config, enabled, created_at
+        FROM notification_channels
+        ORDER BY name
+    "#)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter().map(channel_from_row).collect()
+}
+
+pub async fn update_channel(pool: &Pool<Sqlite>, channel: &NotificationChannel) -> Result<bool> {
+    let result = sqlx::query(r#"
+        UPDATE notification_channels
+        SET name = ?, config = ?, enabled = ?
+        WHERE id = ?
+    "#)
+    .bind(&channel.name)
+    .bind(serde_json::to_string(&channel.config)?)
+    .bind(channel.enabled)
+    .bind(&channel.id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_channel(pool: &Pool<Sqlite>, channel_id: &str) -> Result<bool> {
+    let result = sqlx::query(r#"
+        DELETE FROM notification_channels WHERE id = ?
+    "#)
+    .bind(channel_id)
+    .execute(pool)
+    .await?;
+
+    sqlx::query(r#"
+        DELETE FROM task_notifications WHERE channel_id = ?
+    "#)
+    .bind(channel_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_task_notifications(pool: &Pool<Sqlite>, task_id: &str) -> Result<Vec<TaskNotification>> {
+    let rows = sqlx::query_as::<_, (String, String)>(r#"
+        SELECT channel_id, notify_on
+        FROM task_notifications
+        WHERE task_id = ?
+    "#)
+    .bind(task_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(channel_id, notify_on)| {
+            NotifyOn::parse(&notify_on).map(|notify_on| TaskNotification { channel_id, notify_on })
+        })
+        .collect())
+}
+
+/// Replace all channel subscriptions of a task
+pub async fn set_task_notifications(pool: &Pool<Sqlite>, task_id: &str, notifications: &[TaskNotification]) -> Result<()> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query(r#"
+        DELETE FROM task_notifications WHERE task_id = ?
+    "#)
+    .bind(task_id)
+    .execute(&mut *transaction)
+    .await?;
+
+    for notification in notifications {
+        sqlx::query(r#"
+            INSERT INTO task_notifications (task_id, channel_id, notify_on)
+            VALUES (?, ?, ?)
+        "#)
+        .bind(task_id)
+        .bind(&notification.channel_id)
+        .bind(notification.notify_on.as_str())
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Enabled channels a task is subscribed to, with the results they want
+pub async fn get_subscribed_channels(pool: &Pool<Sqlite>, task_name: &str) -> Result<Vec<(NotificationChannel, NotifyOn)>> {
+    let rows = sqlx::query_as::<_, (String, String, String, bool, DateTime<Utc>, String)>(r#"
+        SELECT c.id, c.name, c.config, c.enabled, c.created_at, n.notify_on
+        FROM task_notifications n
+        JOIN tasks t ON t.id = n.task_id
+        JOIN notification_channels c ON c.id = n.channel_id
+        WHERE t.name = ? AND c.enabled
+    "#)
+    .bind(task_name)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .filter_map(|(id, name, config, enabled, created_at, notify_on)| {
+            let notify_on = NotifyOn::parse(&notify_on)?;
+            Some(channel_from_row((id, name, config, enabled, created_at)).map(|channel| (channel, notify_on)))
+        })
+        .collect()
+}
+
+pub async fn count_jobs_by_status(pool: &Pool<Sqlite>) -> Result<Vec<(String, i64)>> {
+    let counts = sqlx::query_as::<_, (String, i64)>(r#"
+        SELECT status, COUNT(*) FROM jobs GROUP BY status
+    "#)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(counts)
+}
+
+/// End time of the most recent completed job of every task, including jobs removed by the retention policy
+pub async fn get_last_task_successes(pool: &Pool<Sqlite>) -> Result<Vec<(String, i64)>> {
+    let successes = sqlx::query_as::<_, (String, i64)>(r#"
+        SELECT task_name, MAX(end_time)
+        FROM (
+            SELECT task_name, end_time
+            FROM jobs
+            WHERE task_name IS NOT NULL AND status = 'Completed' AND end_time IS NOT NULL
+            UNION ALL
+            SELECT task_name, last_success
+            FROM job_stats
+            WHERE task_name IS NOT NULL AND last_success IS NOT NULL
+        )
+        GROUP BY task_name
+    "#)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(successes)
+}
+
+/// Per period aggregates of finished jobs: key, period, jobs, succeeded, failed,
+/// bytes, files, run time and the bytes of jobs with a measurable run time
+pub type HistoryRow = (Option<String>, String, i64, i64, i64, i64, i64, i64, i64);
+
+/// Aggregate finished jobs started in `[since, until)` by task or remote and period (UTC).
+/// Jobs removed by the retention policy are included from `job_stats` by the day they started.
+pub async fn get_transfer_history(
+    pool: &Pool<Sqlite>,
+    group_by: StatsGroupBy,
+    interval: StatsInterval,
+    since: i64,
+    until: i64,
+    task: Option<&str>,
+    remote: Option<&str>,
+) -> Result<Vec<HistoryRow>> {
+    let key = match group_by {
+        StatsGroupBy::Task => "task_name",
+        StatsGroupBy::Remote => "remote_name",
+        StatsGroupBy::None => "NULL",
+    };
+    let period = match interval {
+        StatsInterval::Day => "%Y-%m-%d",
+        StatsInterval::Week => "%Y-W%W",
+        StatsInterval::Month => "%Y-%m",
+    };
+
+    let rows = sqlx::query_as::<_, HistoryRow>(&format!(r#"
+        WITH finished AS (
+            SELECT start_time, task_name, remote_name, 1 AS jobs,
+                   status = 'Completed' AS succeeded,
+                   status LIKE 'Failed%' OR status LIKE 'Error%' AS failed,
+                   transferred AS bytes, files, end_time - start_time AS duration_secs,
+                   CASE WHEN end_time > start_time THEN transferred ELSE 0 END AS timed_bytes
+            FROM jobs
+            WHERE end_time IS NOT NULL
+            UNION ALL
+            SELECT day, task_name, remote_name, jobs, succeeded, failed, bytes, files, duration_secs, timed_bytes
+            FROM job_stats
+        )
+        SELECT {key} AS series,
+               strftime('{period}', start_time, 'unixepoch') AS period,
+               SUM(jobs),
+               SUM(succeeded),
+               SUM(failed),
+               SUM(bytes),
+               SUM(files),
+               SUM(duration_secs),
+               SUM(timed_bytes)
+        FROM finished
+        WHERE start_time >= ? AND start_time < ?
+          AND (? IS NULL OR task_name = ?)
+          AND (? IS NULL OR remote_name = ?)
+        GROUP BY series, period
+        ORDER BY series, period
+    "#))
+    .bind(since)
+    .bind(until)
+    .bind(task)
+    .bind(task)
+    .bind(remote)
+    .bind(remote)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
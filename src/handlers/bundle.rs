@@ -0,0 +1,67 @@
+use axum::{extract::Json, response::Json as ResponseJson, Extension};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::sync::Arc;
+use tracing::{info, error};
+use crate::bundle::{self, Bundle, ImportReport, MergeStrategy};
+use crate::config_manager::ConfigManager;
+use crate::models::ApiResponse;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExportRequest {
+    pub passphrase: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportRequest {
+    pub bundle: Bundle,
+    #[serde(default)]
+    pub strategy: MergeStrategy,
+    #[serde(default)]
+    pub dry_run: bool,
+    pub passphrase: Option<String>,
+}
+
+pub async fn export_bundle(
+    Extension(config_manager): Extension<Arc<ConfigManager>>,
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Json(request): Json<ExportRequest>,
+) -> ResponseJson<ApiResponse<Bundle>> {
+    info!("📦 Exporting configuration bundle");
+
+    match bundle::export_bundle(&config_manager, &pool, request.passphrase.as_deref()).await {
+        Ok(bundle) => {
+            info!("✅ Exported {} remotes and {} tasks", bundle.remotes.len(), bundle.tasks.len());
+            ResponseJson(ApiResponse::success(bundle))
+        }
+        Err(e) => {
+            error!("Failed to export bundle: {}", e);
+            ResponseJson(ApiResponse::error(&e.to_string()))
+        }
+    }
+}
+
+pub async fn import_bundle(
+    Extension(config_manager): Extension<Arc<ConfigManager>>,
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Json(request): Json<ImportRequest>,
+) -> ResponseJson<ApiResponse<ImportReport>> {
+    info!("📦 Importing configuration bundle (strategy: {:?}, dry run: {})", request.strategy, request.dry_run);
+
+    match bundle::import_bundle(
+        &config_manager,
+        &pool,
+        request.bundle,
+        request.strategy,
+        request.passphrase.as_deref(),
+        request.dry_run,
+    )
+    .await
+    {
+        Ok(report) => ResponseJson(ApiResponse::success(report)),
+        Err(e) => {
+            error!("Failed to import bundle: {}", e);
+            ResponseJson(ApiResponse::error(&e.to_string()))
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use axum::{extract::Query, response::Json as ResponseJson, Extension};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+use crate::handlers::files::{format_timestamp, DEFAULT_LIST_LIMIT, hash_local_file, remote_spec, run_rclone};
+use crate::models::{ApiResponse, CompareEntry, CompareRequest, CompareState, DirComparison};
+use crate::settings::Settings;
+
+/// How long a comparison is reused for paging through its results
+const CACHE_TTL_SECS: i64 = 300;
+
+/// Modification times closer than this are considered equal, many backends only store seconds
+const MODIFY_WINDOW_SECS: i64 = 1;
+
+/// Hash types computed for local files, in order of preference
+const LOCAL_HASHES: [&str; 2] = ["md5", "sha1"];
+
+/// Local path, remote, remote path and whether hashes were compared
+type CompareKey = (String, String, String, bool);
+
+struct CachedComparison {
+    compared_at: i64,
+    entries: Vec<CompareEntry>,
+}
+
+/// A comparison is locked while it is computed, so concurrent requests for it wait instead of comparing again
+type ComparisonSlot = Arc<Mutex<Option<CachedComparison>>>;
+
+lazy_static::lazy_static! {
+    static ref COMPARISONS: Mutex<HashMap<CompareKey, ComparisonSlot>> = Mutex::new(HashMap::new());
+}
+
+/// A file or directory on one side of the comparison
+#[derive(Debug, Clone)]
+struct SideEntry {
+    is_dir: bool,
+    size: Option<u64>,
+    modified: Option<DateTime<Utc>>,
+    hashes: HashMap<String, String>,
+}
+
+/// Compare a local directory with a remote path. The whole tree is compared once and
+/// cached, so the result can be paged through with `offset`/`limit` and filtered by `states`.
+pub async fn compare_directories(
+    Extension(settings): Extension<Arc<Settings>>,
+    Query(request): Query<CompareRequest>,
+) -> ResponseJson<ApiResponse<DirComparison>> {
+    let states = match parse_states(request.states.as_deref()) {
+        Ok(states) => states,
+        Err(e) => return ResponseJson(ApiResponse::error(&e.to_string())),
+    };
+
+    let key = (
+        request.local_path.clone(),
+        request.remote.clone(),
+        request.remote_path.clone(),
+        request.hash,
+    );
+    let now = Utc::now().timestamp();
+
+    // The map is only locked to find the slot, comparisons of other paths run in parallel
+    let slot = {
+        let mut comparisons = COMPARISONS.lock().await;
+        comparisons.retain(|_, slot| match slot.try_lock() {
+            Ok(comparison) => comparison.as_ref().is_some_and(|c| now - c.compared_at < CACHE_TTL_SECS),
+            Err(_) => true,
+        });
+        comparisons.entry(key).or_default().clone()
+    };
+
+    let mut cached = slot.lock().await;
+    let expired = cached.as_ref().is_none_or(|c| Utc::now().timestamp() - c.compared_at >= CACHE_TTL_SECS);
+    if request.refresh || expired {
+        info!("🔍 Comparing {} with {}:{}", request.local_path, request.remote, request.remote_path);
+        match compare(&settings, &request).await {
+            Ok(entries) => {
+                *cached = Some(CachedComparison { compared_at: Utc::now().timestamp(), entries });
+            }
+            Err(e) => return ResponseJson(ApiResponse::error(&e.to_string())),
+        }
+    }
+    let comparison = cached.as_ref().expect("comparison is cached");
+
+    let mut summary = HashMap::new();
+    for entry in &comparison.entries {
+        *summary.entry(entry.state).or_insert(0) += 1;
+    }
+
+    let matching: Vec<&CompareEntry> = comparison
+        .entries
+        .iter()
+        .filter(|entry| states.as_ref().is_none_or(|states| states.contains(&entry.state)))
+        .collect();
+    let total = matching.len();
+    let entries = matching
+        .into_iter()
+        .skip(request.offset)
+        .take(request.limit.unwrap_or(DEFAULT_LIST_LIMIT))
+        .cloned()
+        .collect();
+
+    ResponseJson(ApiResponse::success(DirComparison {
+        local_path: request.local_path,
+        remote: request.remote,
+        remote_path: request.remote_path,
+        compared_at: comparison.compared_at,
+        summary,
+        total,
+        entries,
+    }))
+}
+
+fn parse_states(states: Option<&str>) -> anyhow::Result<Option<Vec<CompareState>>> {
+    let Some(states) = states.filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+
+    states
+        .split(',')
+        .map(|state| {
+            serde_json::from_value(serde_json::Value::String(state.trim().to_string()))
+                .map_err(|_| anyhow::anyhow!("Unknown state '{}'", state.trim()))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map(Some)
+}
+
+async fn compare(settings: &Settings, request: &CompareRequest) -> anyhow::Result<Vec<CompareEntry>> {
+    let local_root = PathBuf::from(&request.local_path);
+    if !local_root.is_dir() {
+        return Err(anyhow::anyhow!("Directory does not exist"));
+    }
+
+    let remote = list_remote_tree(settings, &request.remote, &request.remote_path, request.hash).await?;
+    let root = local_root.clone();
+    let local = tokio::task::spawn_blocking(move || list_local_tree(&root)).await?;
+
+    let mut paths: BTreeMap<&String, (Option<&SideEntry>, Option<&SideEntry>)> = BTreeMap::new();
+    for (path, entry) in &local {
+        paths.entry(path).or_default().0 = Some(entry);
+    }
+    for (path, entry) in &remote {
+        paths.entry(path).or_default().1 = Some(entry);
+    }
+
+    // Hashing is only needed where size alone cannot tell the files apart
+    let to_hash: Vec<String> = if request.hash {
+        paths
+            .iter()
+            .filter_map(|(path, sides)| match sides {
+                (Some(l), Some(r)) if !l.is_dir && !r.is_dir && l.size == r.size && comparable_hash(r).is_some() => {
+                    Some((*path).clone())
+                }
+                _ => None,
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let local_hashes = tokio::task::spawn_blocking(move || {
+        to_hash
+            .into_iter()
+            .filter_map(|path| hash_local_file(&local_root.join(&path)).ok().map(|hashes| (path, hashes)))
+            .collect::<HashMap<_, _>>()
+    })
+    .await?;
+
+    let mut entries = Vec::new();
+    for (path, sides) in paths {
+        let state = match sides {
+            (Some(_), None) => CompareState::OnlyLocal,
+            (None, Some(_)) => CompareState::OnlyRemote,
+            // Directories present on both sides are described by their contents
+            (Some(l), Some(r)) if l.is_dir && r.is_dir => continue,
+            (Some(l), Some(r)) => compare_files(l, r, local_hashes.get(path)),
+            (None, None) => continue,
+        };
+        let (local, remote) = sides;
+
+        entries.push(CompareEntry {
+            path: path.clone(),
+            is_dir: local.or(remote).is_some_and(|e| e.is_dir),
+            state,
+            local_size: local.and_then(|e| e.size),
+            remote_size: remote.and_then(|e| e.size),
+            local_modified: local.and_then(|e| e.modified).map(format_timestamp),
+            remote_modified: remote.and_then(|e| e.modified).map(format_timestamp),
+        });
+    }
+
+    info!("🔍 Compared {} with {}:{}: {} entries", request.local_path, request.remote, request.remote_path, entries.len());
+    Ok(entries)
+}
+
+/// The first remote hash that can also be computed locally
+fn comparable_hash(remote: &SideEntry) -> Option<(&'static str, &String)> {
+    LOCAL_HASHES
+        .iter()
+        .find_map(|name| remote.hashes.get(*name).map(|value| (*name, value)))
+}
+
+/// Size first, then hash if available, then modification time.
+/// A file and a directory with the same name differ in size.
+fn compare_files(local: &SideEntry, remote: &SideEntry, local_hashes: Option<&HashMap<String, String>>) -> CompareState {
+    if local.is_dir != remote.is_dir || local.size != remote.size {
+        return CompareState::DiffersSize;
+    }
+
+    if let (Some((name, remote_hash)), Some(local_hashes)) = (comparable_hash(remote), local_hashes) {
+        return match local_hashes.get(name) {
+            Some(local_hash) if local_hash.eq_ignore_ascii_case(remote_hash) => CompareState::Identical,
+            _ => CompareState::DiffersHash,
+        };
+    }
+
+    match (local.modified, remote.modified) {
+        (Some(l), Some(r)) if (l - r).num_seconds().abs() >= MODIFY_WINDOW_SECS => CompareState::DiffersTime,
+        _ => CompareState::Identical,
+    }
+}
+
+/// All entries below a local directory by relative path. Symlinks are skipped like rclone does.
+fn list_local_tree(root: &Path) -> HashMap<String, SideEntry> {
+    walkdir::WalkDir::new(root)
+        .follow_links(false)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| !entry.path_is_symlink())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let relative = entry.path().strip_prefix(root).ok()?;
+            let path = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            Some((
+                path,
+                SideEntry {
+                    is_dir: metadata.is_dir(),
+                    size: metadata.is_file().then_some(metadata.len()),
+                    modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+                    hashes: HashMap::new(),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// All entries below a remote path by relative path, via `rclone lsjson -R`
+async fn list_remote_tree(
+    settings: &Settings,
+    remote: &str,
+    path: &str,
+    hash: bool,
+) -> anyhow::Result<HashMap<String, SideEntry>> {
+    let target = remote_spec(remote, path)?;
+    let mut args = vec!["-R"];
+    if hash {
+        args.push("--hash");
+    }
+    args.push(&target);
+
+    let output = run_rclone(settings, "lsjson", &args).await?;
+    let entries: Vec<serde_json::Value> = serde_json::from_str(&output)?;
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| {
+            let path = entry["Path"].as_str()?.to_string();
+            let is_dir = entry["IsDir"].as_bool().unwrap_or(false);
+            let hashes = entry["Hashes"]
+                .as_object()
+                .map(|hashes| {
+                    hashes
+                        .iter()
+                        .filter_map(|(name, value)| value.as_str().map(|v| (name.to_lowercase(), v.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            Some((
+                path,
+                SideEntry {
+                    is_dir,
+                    // Directories report -1
+                    size: if is_dir { None } else { entry["Size"].as_u64() },
+                    modified: entry["ModTime"]
+                        .as_str()
+                        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                        .map(|t| t.with_timezone(&Utc)),
+                    hashes,
+                },
+            ))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(size: u64, modified: &str, hashes: &[(&str, &str)]) -> SideEntry {
+        SideEntry {
+            is_dir: false,
+            size: Some(size),
+            modified: Some(DateTime::parse_from_rfc3339(modified).unwrap().with_timezone(&Utc)),
+            hashes: hashes.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    fn hashes(md5: &str) -> HashMap<String, String> {
+        HashMap::from([("md5".to_string(), md5.to_string())])
+    }
+
+    #[test]
+    fn size_is_compared_first() {
+        let local = file(10, "2025-01-01T00:00:00Z", &[]);
+        let remote = file(11, "2025-01-01T00:00:00Z", &[("md5", "aa")]);
+
+        assert_eq!(compare_files(&local, &remote, Some(&hashes("aa"))), CompareState::DiffersSize);
+    }
+
+    #[test]
+    fn file_and_directory_differ_in_size() {
+        let local = file(0, "2025-01-01T00:00:00Z", &[]);
+        let remote = SideEntry { is_dir: true, size: None, modified: None, hashes: HashMap::new() };
+
+        assert_eq!(compare_files(&local, &remote, None), CompareState::DiffersSize);
+    }
+
+    #[test]
+    fn hash_decides_over_modification_time() {
+        let local = file(10, "2025-01-01T00:00:00Z", &[]);
+        let remote = file(10, "2025-06-01T00:00:00Z", &[("md5", "AA")]);
+
+        assert_eq!(compare_files(&local, &remote, Some(&hashes("aa"))), CompareState::Identical);
+        assert_eq!(compare_files(&local, &remote, Some(&hashes("bb"))), CompareState::DiffersHash);
+    }
+
+    #[test]
+    fn modification_time_is_used_without_hashes() {
+        let local = file(10, "2025-01-01T00:00:00.900Z", &[]);
+
+        let within_window = file(10, "2025-01-01T00:00:00Z", &[("md5", "aa")]);
+        assert_eq!(compare_files(&local, &within_window, None), CompareState::Identical);
+
+        let outside_window = file(10, "2025-01-01T00:00:02Z", &[]);
+        assert_eq!(compare_files(&local, &outside_window, Some(&hashes("aa"))), CompareState::DiffersTime);
+    }
+
+    #[test]
+    fn states_are_parsed() {
+        assert_eq!(parse_states(None).unwrap(), None);
+        assert_eq!(parse_states(Some("")).unwrap(), None);
+        assert_eq!(
+            parse_states(Some("differs_size, only_remote")).unwrap(),
+            Some(vec![CompareState::DiffersSize, CompareState::OnlyRemote])
+        );
+        assert_eq!(parse_states(Some("identical,changed")).unwrap_err().to_string(), "Unknown state 'changed'");
+    }
+}
@@ -1,6 +1,6 @@
 use axum::{extract::Json, response::Json as ResponseJson, Extension};
 use std::sync::Arc;
-use crate::models::{ApiResponse, ConfigRequest, RcloneConfig};
+use crate::models::{ApiResponse, ConfigBackup, ConfigRequest, ConfigSource, ConfigSyncStatus, RcloneConfig, ResolveConflictRequest};
 use crate::config_manager::ConfigManager;
 
 pub async fn get_configs(
@@ -69,3 +69,51 @@ pub async fn get_config_for_edit(
     }
 }
 
+pub async fn list_config_backups(
+    Extension(config_manager): Extension<Arc<ConfigManager>>,
+) -> ResponseJson<ApiResponse<Vec<ConfigBackup>>> {
+    match config_manager.list_backups().await {
+        Ok(backups) => ResponseJson(ApiResponse::success(backups)),
+        Err(e) => ResponseJson(ApiResponse::error(&e.to_string())),
+    }
+}
+
+pub async fn restore_config_backup(
+    Extension(config_manager): Extension<Arc<ConfigManager>>,
+    id: String,
+) -> ResponseJson<ApiResponse<String>> {
+    match config_manager.restore_backup(&id).await {
+        Ok(_) => ResponseJson(ApiResponse::success(format!("Configuration restored from backup {}", id))),
+        Err(e) => ResponseJson(ApiResponse::error(&e.to_string())),
+    }
+}
+
+pub async fn get_config_sync_status(
+    Extension(config_manager): Extension<Arc<ConfigManager>>,
+) -> ResponseJson<ApiResponse<ConfigSyncStatus>> {
+    match config_manager.sync_status().await {
+        Ok(status) => ResponseJson(ApiResponse::success(status)),
+        Err(e) => ResponseJson(ApiResponse::error(&e.to_string())),
+    }
+}
+
+pub async fn resolve_config_conflict(
+    Extension(config_manager): Extension<Arc<ConfigManager>>,
+    Json(request): Json<ResolveConflictRequest>,
+) -> ResponseJson<ApiResponse<ConfigSyncStatus>> {
+    let default = request.default.unwrap_or(ConfigSource::Memory);
+    match config_manager.resolve_conflict(&request.choices, default).await {
+        Ok(status) => ResponseJson(ApiResponse::success(status)),
+        Err(e) => ResponseJson(ApiResponse::error(&e.to_string())),
+    }
+}
+
+pub async fn test_config(
+    Extension(config_manager): Extension<Arc<ConfigManager>>,
+    name: String,
+) -> ResponseJson<ApiResponse<String>> {
+    match config_manager.test_remote(&name).await {
+        Ok(message) => ResponseJson(ApiResponse::success(message)),
+        Err(e) => ResponseJson(ApiResponse::error(&e.to_string())),
+    }
+}
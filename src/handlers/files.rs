@@ -1,36 +1,370 @@
-use axum::{extract::Query, response::Json as ResponseJson};
+use axum::{
+    body::Body,
+    extract::{Json, Query},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json as ResponseJson, Response},
+    Extension,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::StreamExt;
+use serde::Deserialize;
+use sqlx::{Pool, Sqlite};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
-use tokio::process::Command;
-use crate::models::{ApiResponse, FileEntry};
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, ChildStdin, Command};
+use tokio_util::io::ReaderStream;
+use tracing::{info, warn, error};
+use crate::handlers::sync;
+use crate::models::{ApiResponse, FileEntry, RemoteDeleteRequest, RemotePathRequest, RemoteTransferRequest, SyncProgress, SyncRequest};
+use crate::settings::Settings;
+
+/// Header carrying the number of entries before `offset`/`limit` are applied
+const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+/// Page size of listings requested without `limit`, large directories would otherwise return everything
+pub(crate) const DEFAULT_LIST_LIMIT: usize = 1000;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    #[default]
+    Name,
+    Size,
+    Mtime,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Query parameters shared by the local and remote listing endpoints
+#[derive(Debug, Default, Deserialize)]
+pub struct ListParams {
+    pub remote: Option<String>,
+    pub path: Option<String>,
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub sort: SortKey,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Only entries whose name contains this text (case-insensitive)
+    pub filter: Option<String>,
+    /// Search the whole tree below `path` for names matching this glob (`*`, `?`) or text
+    pub search: Option<String>,
+    /// Include file hashes (`rclone lsjson --hash` remotely, md5 and sha1 computed locally)
+    #[serde(default)]
+    pub hash: bool,
+}
+
+type Listing = (HeaderMap, ResponseJson<ApiResponse<Vec<FileEntry>>>);
+
+/// Buffer size used when hashing local files
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
 
 pub async fn list_local_files(
-    Query(params): Query<HashMap<String, String>>,
-) -> ResponseJson<ApiResponse<Vec<FileEntry>>> {
+    Query(params): Query<ListParams>,
+) -> Listing {
     let default_path = std::env::var("RCLONE_GUI_DEFAULT_PATH").unwrap_or_else(|_| "/mnt/home".to_string());
-    let path = params.get("path").unwrap_or(&default_path).clone();
+    let path = params.path.clone().unwrap_or(default_path);
     
-    match list_directory(&path).await {
-        Ok(files) => ResponseJson(ApiResponse::success(files)),
-        Err(e) => ResponseJson(ApiResponse::error(&e.to_string())),
+    let result = match params.search.as_deref().filter(|s| !s.is_empty()) {
+        Some(search) => search_directory(path, search.to_string()).await,
+        None => list_directory(&path).await,
+    };
+
+    let files = match result {
+        Ok(files) => files,
+        Err(e) => return (HeaderMap::new(), ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+
+    let (mut files, total) = apply_list_params(files, &params);
+
+    // Hash only the returned page, hashing a whole directory can take very long
+    if params.hash {
+        files = match tokio::task::spawn_blocking(move || add_local_hashes(files)).await {
+            Ok(files) => files,
+            Err(e) => return (HeaderMap::new(), ResponseJson(ApiResponse::error(&e.to_string()))),
+        };
     }
+
+    listing_response(files, total)
 }
 
 pub async fn list_remote_files(
-    Query(params): Query<HashMap<String, String>>,
-) -> ResponseJson<ApiResponse<Vec<FileEntry>>> {
-    let remote_name = match params.get("remote") {
+    Extension(settings): Extension<Arc<Settings>>,
+    Query(params): Query<ListParams>,
+) -> Listing {
+    let remote_name = match &params.remote {
         Some(name) => name,
-        None => return ResponseJson(ApiResponse::error("Remote name is required")),
+        None => return (HeaderMap::new(), ResponseJson(ApiResponse::error("Remote name is required"))),
     };
     
-    let remote_path = params.get("path").unwrap_or(&"/".to_string()).clone();
+    let remote_path = params.path.clone().unwrap_or_else(|| "/".to_string());
+    let search = params.search.as_deref().filter(|s| !s.is_empty());
     
-    match list_remote_directory(remote_name, &remote_path).await {
-        Ok(files) => ResponseJson(ApiResponse::success(files)),
-        Err(e) => ResponseJson(ApiResponse::error(&e.to_string())),
+    match list_remote_directory(&settings, remote_name, &remote_path, search, params.hash).await {
+        Ok(files) => {
+            let (files, total) = apply_list_params(files, &params);
+            listing_response(files, total)
+        }
+        Err(e) => (HeaderMap::new(), ResponseJson(ApiResponse::error(&e.to_string()))),
+    }
+}
+
+/// Filter, sort and paginate a listing. Directories always come first.
+/// Returns the page and the number of entries before pagination.
+fn apply_list_params(mut files: Vec<FileEntry>, params: &ListParams) -> (Vec<FileEntry>, usize) {
+    if let Some(filter) = params.filter.as_deref().filter(|f| !f.is_empty()) {
+        let filter = filter.to_lowercase();
+        files.retain(|f| f.name.to_lowercase().contains(&filter));
+    }
+
+    files.sort_by(|a, b| {
+        let ordering = match params.sort {
+            SortKey::Name => a.name.cmp(&b.name),
+            SortKey::Size => a.size.cmp(&b.size).then_with(|| a.name.cmp(&b.name)),
+            SortKey::Mtime => modified_timestamp(a).cmp(&modified_timestamp(b)).then_with(|| a.name.cmp(&b.name)),
+        };
+        let ordering = match params.order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        };
+        b.is_dir.cmp(&a.is_dir).then(ordering)
+    });
+
+    let total = files.len();
+    let files = files
+        .into_iter()
+        .skip(params.offset)
+        .take(params.limit.unwrap_or(DEFAULT_LIST_LIMIT))
+        .collect();
+
+    (files, total)
+}
+
+fn listing_response(files: Vec<FileEntry>, total: usize) -> Listing {
+    let mut headers = HeaderMap::new();
+    headers.insert(TOTAL_COUNT_HEADER, total.into());
+    (headers, ResponseJson(ApiResponse::success(files)))
+}
+
+fn modified_timestamp(file: &FileEntry) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(file.modified.as_deref()?)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Normalized form of all `FileEntry.modified` timestamps
+pub(crate) fn format_timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Case-insensitive match of a file name against a glob (`*`, `?`) or, without wildcards, a substring
+fn name_matches(pattern: &str, name: &str) -> bool {
+    let (pattern, name) = (pattern.to_lowercase(), name.to_lowercase());
+    if !is_glob(&pattern) {
+        return name.contains(&pattern);
+    }
+
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // Iterative wildcard matching with backtracking to the last `*`
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Build the entry of a local path. Symbolic links are described by their target
+/// (so links to directories can be browsed) and report where they point to.
+fn local_entry(path: &Path) -> std::io::Result<FileEntry> {
+    let link_metadata = fs::symlink_metadata(path)?;
+    let symlink_target = if link_metadata.file_type().is_symlink() {
+        fs::read_link(path).ok().map(|target| target.to_string_lossy().to_string())
+    } else {
+        None
+    };
+    // Broken links are listed with the metadata of the link itself
+    let metadata = match symlink_target {
+        Some(_) => fs::metadata(path).unwrap_or(link_metadata),
+        None => link_metadata,
+    };
+
+    let size = if metadata.is_file() {
+        Some(metadata.len())
+    } else {
+        None
+    };
+
+    let modified = metadata.modified()
+        .ok()
+        .map(|time| format_timestamp(DateTime::<Utc>::from(time)));
+
+    let mime_type = if metadata.is_dir() {
+        "inode/directory".to_string()
+    } else {
+        mime_guess::from_path(path).first_or_octet_stream().to_string()
+    };
+
+    let (permissions, owner, group) = unix_ownership(&metadata);
+
+    Ok(FileEntry {
+        name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        path: path.to_string_lossy().to_string(),
+        is_dir: metadata.is_dir(),
+        size,
+        modified,
+        mime_type: Some(mime_type),
+        symlink_target,
+        permissions,
+        owner,
+        group,
+        hashes: None,
+    })
+}
+
+#[cfg(unix)]
+fn unix_ownership(metadata: &fs::Metadata) -> (Option<String>, Option<String>, Option<String>) {
+    use std::os::unix::fs::MetadataExt;
+
+    let mode = metadata.mode();
+    let permissions = ["r", "w", "x"]
+        .iter()
+        .cycle()
+        .take(9)
+        .enumerate()
+        .map(|(i, flag)| if mode & (0o400 >> i) != 0 { *flag } else { "-" })
+        .collect();
+
+    (Some(permissions), Some(user_name(metadata.uid())), Some(group_name(metadata.gid())))
+}
+
+#[cfg(not(unix))]
+fn unix_ownership(_metadata: &fs::Metadata) -> (Option<String>, Option<String>, Option<String>) {
+    (None, None, None)
+}
+
+#[cfg(unix)]
+lazy_static::lazy_static! {
+    static ref USER_NAMES: std::sync::Mutex<HashMap<u32, String>> = std::sync::Mutex::new(HashMap::new());
+    static ref GROUP_NAMES: std::sync::Mutex<HashMap<u32, String>> = std::sync::Mutex::new(HashMap::new());
+}
+
+/// Name of a user id, or the id itself if it has no passwd entry
+#[cfg(unix)]
+fn user_name(uid: u32) -> String {
+    let mut cache = USER_NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .entry(uid)
+        .or_insert_with(|| {
+            let mut buffer = vec![0 as libc::c_char; 4096];
+            // SAFETY: `libc::passwd` is a plain C struct of integers and pointers, all-zero is a valid value
+            let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+            let mut result = std::ptr::null_mut();
+            // SAFETY: `buffer.len()` is the real size of `buffer`, so `getpwuid_r` never writes past it
+            // (it fails with ERANGE instead, and the id is shown). `entry`, `buffer` and `result`
+            // outlive the call
+            let status = unsafe { libc::getpwuid_r(uid, &mut entry, buffer.as_mut_ptr(), buffer.len(), &mut result) };
+            if status == 0 && !result.is_null() {
+                // SAFETY: on success `pw_name` points to a NUL-terminated string inside `buffer`,
+                // which is still alive here; the name is copied before `buffer` is dropped
+                unsafe { std::ffi::CStr::from_ptr(entry.pw_name) }.to_string_lossy().to_string()
+            } else {
+                uid.to_string()
+            }
+        })
+        .clone()
+}
+
+/// Name of a group id, or the id itself if it has no group entry
+#[cfg(unix)]
+fn group_name(gid: u32) -> String {
+    let mut cache = GROUP_NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .entry(gid)
+        .or_insert_with(|| {
+            let mut buffer = vec![0 as libc::c_char; 4096];
+            // SAFETY: `libc::group` is a plain C struct of integers and pointers, all-zero is a valid value
+            let mut entry: libc::group = unsafe { std::mem::zeroed() };
+            let mut result = std::ptr::null_mut();
+            // SAFETY: `buffer.len()` is the real size of `buffer`, so `getgrgid_r` never writes past it
+            // (it fails with ERANGE instead, and the id is shown). `entry`, `buffer` and `result`
+            // outlive the call
+            let status = unsafe { libc::getgrgid_r(gid, &mut entry, buffer.as_mut_ptr(), buffer.len(), &mut result) };
+            if status == 0 && !result.is_null() {
+                // SAFETY: on success `gr_name` points to a NUL-terminated string inside `buffer`,
+                // which is still alive here; the name is copied before `buffer` is dropped
+                unsafe { std::ffi::CStr::from_ptr(entry.gr_name) }.to_string_lossy().to_string()
+            } else {
+                gid.to_string()
+            }
+        })
+        .clone()
+}
+
+/// md5 and sha1 of a local file, the hash types most rclone backends support
+pub(crate) fn hash_local_file(path: &Path) -> std::io::Result<HashMap<String, String>> {
+    use md5::{Digest, Md5};
+    use sha1::Sha1;
+    use std::io::Read;
+
+    let mut file = fs::File::open(path)?;
+    let mut md5 = Md5::new();
+    let mut sha1 = Sha1::new();
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        md5.update(&buffer[..read]);
+        sha1.update(&buffer[..read]);
     }
+
+    Ok(HashMap::from([
+        ("md5".to_string(), format!("{:x}", md5.finalize())),
+        ("sha1".to_string(), format!("{:x}", sha1.finalize())),
+    ]))
+}
+
+fn add_local_hashes(mut files: Vec<FileEntry>) -> Vec<FileEntry> {
+    for file in files.iter_mut().filter(|f| !f.is_dir) {
+        match hash_local_file(Path::new(&file.path)) {
+            Ok(hashes) => file.hashes = Some(hashes),
+            Err(e) => warn!("Could not hash {}: {}", file.path, e),
+        }
+    }
+    files
 }
 
 async fn list_directory(path: &str) -> anyhow::Result<Vec<FileEntry>> {
@@ -43,68 +377,94 @@ async fn list_directory(path: &str) -> anyhow::Result<Vec<FileEntry>> {
     
     for entry in fs::read_dir(path)? {
         let entry = entry?;
-        let metadata = entry.metadata()?;
-        let file_name = entry.file_name().to_string_lossy().to_string();
-        let file_path = entry.path().to_string_lossy().to_string();
-        
-        let size = if metadata.is_file() {
-            Some(metadata.len())
-        } else {
-            None
-        };
+        files.push(local_entry(&entry.path())?);
+    }
 
-        let modified = metadata.modified()
-            .ok()
-            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
-            .map(|duration| duration.as_secs().to_string());
+    Ok(files)
+}
 
-        files.push(FileEntry {
-            name: file_name,
-            path: file_path,
-            is_dir: metadata.is_dir(),
-            size,
-            modified,
-        });
+/// Recursively search a local directory for names matching `pattern`
+async fn search_directory(path: String, pattern: String) -> anyhow::Result<Vec<FileEntry>> {
+    if !Path::new(&path).is_dir() {
+        return Err(anyhow::anyhow!("Directory does not exist"));
     }
 
-    files.sort_by(|a, b| {
-        if a.is_dir && !b.is_dir {
-            std::cmp::Ordering::Less
-        } else if !a.is_dir && b.is_dir {
-            std::cmp::Ordering::Greater
-        } else {
-            a.name.cmp(&b.name)
-        }
-    });
+    let files = tokio::task::spawn_blocking(move || {
+        walkdir::WalkDir::new(&path)
+            .follow_links(false)
+            .min_depth(1)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| name_matches(&pattern, &entry.file_name().to_string_lossy()))
+            .filter_map(|entry| local_entry(entry.path()).ok())
+            .collect()
+    })
+    .await?;
 
     Ok(files)
 }
 
-async fn list_remote_directory(remote_name: &str, remote_path: &str) -> anyhow::Result<Vec<FileEntry>> {
-    let remote_full_path = format!("{}:{}", remote_name, remote_path);
-    let config_path = "data/cfg/rclone.conf";
-    
-    let output = Command::new("rclone")
-        .args(&["lsjson", "--config", config_path, &remote_full_path])
-        .output()
-        .await?;
+/// rclone filter rule matching file names anywhere below the listed directory.
+/// Filter metacharacters other than `*` and `?` are escaped.
+fn rclone_name_filter(search: &str) -> String {
+    let mut escaped = String::new();
+    for c in search.chars() {
+        if matches!(c, '{' | '}' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
 
-    if !output.status.success() {
-        let error = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow::anyhow!("rclone error: {}", error));
+    if is_glob(search) {
+        escaped
+    } else {
+        format!("*{}*", escaped)
     }
+}
+
+async fn list_remote_directory(
+    settings: &Settings,
+    remote_name: &str,
+    remote_path: &str,
+    search: Option<&str>,
+    hash: bool,
+) -> anyhow::Result<Vec<FileEntry>> {
+    let remote_full_path = remote_spec(remote_name, remote_path)?;
+
+    let filter;
+    let mut args = Vec::new();
+    if let Some(search) = search {
+        filter = rclone_name_filter(search);
+        args.extend(["-R", "--ignore-case", "--include", &filter]);
+    }
+    if hash {
+        args.push("--hash");
+    }
+    args.push(&remote_full_path);
 
-    let json_output = String::from_utf8_lossy(&output.stdout);
+    let json_output = run_rclone(settings, "lsjson", &args).await?;
     let entries: Vec<serde_json::Value> = serde_json::from_str(&json_output)?;
     
     let mut files = Vec::new();
     
     for entry in entries {
         let name = entry["Name"].as_str().unwrap_or("").to_string();
-        let path = format!("{}/{}", remote_path.trim_end_matches('/'), name);
+        // Recursive listings report the path relative to the listed directory
+        let relative_path = entry["Path"].as_str().unwrap_or(&name);
+        let path = format!("{}/{}", remote_path.trim_end_matches('/'), relative_path);
         let is_dir = entry["IsDir"].as_bool().unwrap_or(false);
         let size = entry["Size"].as_u64();
-        let modified = entry["ModTime"].as_str().map(|s| s.to_string());
+        let modified = entry["ModTime"]
+            .as_str()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| format_timestamp(t.with_timezone(&Utc)));
+        let mime_type = entry["MimeType"].as_str().map(|s| s.to_string());
+        let hashes = entry["Hashes"].as_object().map(|hashes| {
+            hashes
+                .iter()
+                .filter_map(|(algorithm, value)| value.as_str().map(|v| (algorithm.clone(), v.to_string())))
+                .collect()
+        });
         
         files.push(FileEntry {
             name,
@@ -112,8 +472,372 @@ async fn list_remote_directory(remote_name: &str, remote_path: &str) -> anyhow::
             is_dir,
             size,
             modified,
+            mime_type,
+            symlink_target: None,
+            permissions: None,
+            owner: None,
+            group: None,
+            hashes,
         });
     }
 
     Ok(files)
-}
\ No newline at end of file
+}
+
+pub async fn make_remote_directory(
+    Extension(settings): Extension<Arc<Settings>>,
+    Json(request): Json<RemotePathRequest>,
+) -> ResponseJson<ApiResponse<String>> {
+    let target = match remote_spec(&request.remote, &request.path) {
+        Ok(target) => target,
+        Err(e) => return ResponseJson(ApiResponse::error(&e.to_string())),
+    };
+
+    info!("📁 Creating remote directory {}", target);
+    match run_rclone(&settings, "mkdir", &[&target]).await {
+        Ok(_) => ResponseJson(ApiResponse::success(format!("Directory {} created", target))),
+        Err(e) => {
+            error!("Failed to create remote directory {}: {}", target, e);
+            ResponseJson(ApiResponse::error(&e.to_string()))
+        }
+    }
+}
+
+/// Delete a single file (`rclone deletefile`) or a directory with all its contents (`rclone purge`).
+/// The request must repeat the path in `confirm`.
+pub async fn delete_remote_path(
+    Extension(settings): Extension<Arc<Settings>>,
+    Json(request): Json<RemoteDeleteRequest>,
+) -> ResponseJson<ApiResponse<String>> {
+    if request.confirm != request.path {
+        return ResponseJson(ApiResponse::error("Confirmation does not match the path to delete"));
+    }
+
+    if request.purge && request.path.trim_matches('/').is_empty() {
+        return ResponseJson(ApiResponse::error("Refusing to purge the root of a remote"));
+    }
+
+    let target = match remote_spec(&request.remote, &request.path) {
+        Ok(target) => target,
+        Err(e) => return ResponseJson(ApiResponse::error(&e.to_string())),
+    };
+
+    let command = if request.purge { "purge" } else { "deletefile" };
+    warn!("🗑️ Deleting remote {} ({})", target, command);
+
+    match run_rclone(&settings, command, &[&target]).await {
+        Ok(_) => ResponseJson(ApiResponse::success(format!("{} deleted", target))),
+        Err(e) => {
+            error!("Failed to delete {}: {}", target, e);
+            ResponseJson(ApiResponse::error(&e.to_string()))
+        }
+    }
+}
+
+/// Move or rename a file or directory, also to another remote (`rclone moveto`)
+pub async fn move_remote_path(
+    Extension(settings): Extension<Arc<Settings>>,
+    Json(request): Json<RemoteTransferRequest>,
+) -> ResponseJson<ApiResponse<String>> {
+    transfer_remote_path(&settings, "moveto", &request).await
+}
+
+/// Copy a file or directory, also to another remote (`rclone copyto`)
+pub async fn copy_remote_path(
+    Extension(settings): Extension<Arc<Settings>>,
+    Json(request): Json<RemoteTransferRequest>,
+) -> ResponseJson<ApiResponse<String>> {
+    transfer_remote_path(&settings, "copyto", &request).await
+}
+
+async fn transfer_remote_path(settings: &Settings, command: &str, request: &RemoteTransferRequest) -> ResponseJson<ApiResponse<String>> {
+    let (source, destination) = match (
+        remote_spec(&request.src_remote, &request.src_path),
+        remote_spec(&request.dst_remote, &request.dst_path),
+    ) {
+        (Ok(source), Ok(destination)) => (source, destination),
+        (Err(e), _) | (_, Err(e)) => return ResponseJson(ApiResponse::error(&e.to_string())),
+    };
+
+    if source == destination {
+        return ResponseJson(ApiResponse::error("Source and destination are the same"));
+    }
+
+    info!("🔀 rclone {} {} -> {}", command, source, destination);
+    match run_rclone(settings, command, &[&source, &destination]).await {
+        Ok(_) => ResponseJson(ApiResponse::success(destination)),
+        Err(e) => {
+            error!("Failed to {} {} to {}: {}", command, source, destination, e);
+            ResponseJson(ApiResponse::error(&e.to_string()))
+        }
+    }
+}
+
+/// Stream a remote file to the client through `rclone cat`
+pub async fn download_remote_file(
+    Extension(settings): Extension<Arc<Settings>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    let (Some(remote), Some(path)) = (params.get("remote"), params.get("path")) else {
+        return download_error(StatusCode::BAD_REQUEST, "Remote name and path are required");
+    };
+
+    let target = match remote_spec(remote, path) {
+        Ok(target) => target,
+        Err(e) => return download_error(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
+
+    // Stat first, so a missing file is reported properly instead of as an empty download
+    let stat = match run_rclone(&settings, "lsjson", &["--stat", &target]).await {
+        Ok(output) => serde_json::from_str::<serde_json::Value>(&output).unwrap_or_default(),
+        Err(e) => return download_error(StatusCode::NOT_FOUND, &e.to_string()),
+    };
+
+    if stat["IsDir"].as_bool().unwrap_or(false) {
+        return download_error(StatusCode::BAD_REQUEST, "Only files can be downloaded");
+    }
+
+    let config_path = settings.rclone_config_arg();
+    let mut child = match Command::new(&settings.rclone_binary)
+        .args(["cat", "--config", &config_path, &target])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            error!("Failed to spawn rclone cat for {}: {}", target, e);
+            return download_error(StatusCode::INTERNAL_SERVER_ERROR, &format!("Failed to start rclone: {}", e));
+        }
+    };
+
+    let Some(stdout) = child.stdout.take() else {
+        return download_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to read rclone output");
+    };
+
+    // rclone exits on its own once the file is sent or the client disconnects (broken pipe)
+    let download_target = target.clone();
+    tokio::spawn(async move {
+        match child.wait().await {
+            Ok(status) if status.success() => info!("📥 Download of {} finished", download_target),
+            Ok(status) => warn!("📥 Download of {} ended with exit code {:?}", download_target, status.code()),
+            Err(e) => error!("📥 Download of {} failed: {}", download_target, e),
+        }
+    });
+
+    let file_name = stat["Name"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| path.rsplit('/').next().unwrap_or("download").to_string())
+        .replace(['"', '\\'], "_");
+
+    let mut response = Response::builder()
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file_name));
+    if let Some(size) = stat["Size"].as_u64() {
+        response = response.header(header::CONTENT_LENGTH, size);
+    }
+
+    info!("📥 Streaming {} to client", target);
+    response
+        .body(Body::from_stream(ReaderStream::new(stdout)))
+        .unwrap_or_else(|e| download_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()))
+}
+
+/// Upload the request body to `remote:path` by piping it into `rclone rcat`.
+/// The upload is tracked as a job in the sync job list and can be cancelled like a sync job.
+pub async fn upload_remote_file(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Extension(settings): Extension<Arc<Settings>>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    body: Body,
+) -> ResponseJson<ApiResponse<SyncProgress>> {
+    let (Some(remote), Some(path)) = (params.get("remote"), params.get("path")) else {
+        return ResponseJson(ApiResponse::error("Remote name and path are required"));
+    };
+
+    let target = match remote_spec(remote, path) {
+        Ok(target) => target,
+        Err(e) => return ResponseJson(ApiResponse::error(&e.to_string())),
+    };
+
+    let file_name = path.rsplit('/').next().unwrap_or_default();
+    if file_name.is_empty() {
+        return ResponseJson(ApiResponse::error("Path must include a file name"));
+    }
+
+    let content_length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if content_length.is_some_and(|length| length > settings.max_upload_size) {
+        return ResponseJson(ApiResponse::error(&format!(
+            "Upload exceeds the limit of {} bytes", settings.max_upload_size
+        )));
+    }
+
+    let sync_request = SyncRequest {
+        source_path: format!("upload://{}", file_name),
+        remote_name: remote.clone(),
+        remote_path: path.clone(),
+        chunk_size: None,
+        use_chunking: None,
+        task_name: None,
+        files_from: None,
+        bwlimit: None,
+        retry: None,
+    };
+    let job_id = sync::register_job(&pool, &settings, &sync_request, "Running", content_length.unwrap_or(0)).await;
+    info!("📤 Upload job {} started: {}", job_id, target);
+
+    let config_path = settings.rclone_config_arg();
+    let log_file_path = settings.job_log_path(&job_id).to_string_lossy().to_string();
+    let size_arg = content_length.map(|length| length.to_string());
+
+    let mut args = vec![
+        "rcat",
+        "--config", &config_path,
+        "--use-json-log",
+        "--log-file", &log_file_path,
+        "--log-level", "INFO",
+    ];
+    if let Some(size) = &size_arg {
+        args.extend(["--size", size]);
+    }
+    if let Some(bwlimit) = &settings.bwlimit {
+        args.extend(["--bwlimit", bwlimit]);
+    }
+    args.push(&target);
+
+    let mut child = match Command::new(&settings.rclone_binary)
+        .args(&args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            let status = format!("{}: {}", sync::SPAWN_FAILED_STATUS, e);
+            error!("❌ {}", status);
+            sync::finish_job(&pool, &sync_request, &job_id, &status, None).await;
+            return ResponseJson(ApiResponse::error(&status));
+        }
+    };
+    sync::set_job_pid(&pool, &sync_request, &job_id, child.id()).await;
+
+    let Some(stdin) = child.stdin.take() else {
+        let _ = child.kill().await;
+        sync::finish_job(&pool, &sync_request, &job_id, "Failed", None).await;
+        return ResponseJson(ApiResponse::error("Failed to pass data to rclone"));
+    };
+
+    // The upload runs in its own task, so it is finished even if the client disconnects and this
+    // handler is dropped. rclone is killed before its stdin is closed, it would store a truncated file otherwise.
+    let upload = tokio::spawn(stream_upload(pool, settings, sync_request, job_id.clone(), child, stdin, body));
+    let (status, progress, transferred) = match upload.await {
+        Ok(result) => result,
+        Err(e) => {
+            error!("❌ Upload job {} failed: {}", job_id, e);
+            return ResponseJson(ApiResponse::error("Upload aborted"));
+        }
+    };
+
+    match progress {
+        Some(progress) if progress.status == "Completed" => {
+            info!("✅ Upload job {} completed: {} bytes to {}", job_id, transferred, target);
+            ResponseJson(ApiResponse::success(progress))
+        }
+        Some(progress) => {
+            warn!("❌ Upload job {} ended with status {}", job_id, progress.status);
+            ResponseJson(ApiResponse { success: false, error: Some(progress.status.clone()), data: Some(progress) })
+        }
+        None => ResponseJson(ApiResponse::error(&status)),
+    }
+}
+
+/// Stream the request body into rclone, enforcing the size limit on the actual data, and finish the job
+async fn stream_upload(
+    pool: Pool<Sqlite>,
+    settings: Arc<Settings>,
+    sync_request: SyncRequest,
+    job_id: String,
+    mut child: Child,
+    mut stdin: ChildStdin,
+    body: Body,
+) -> (String, Option<SyncProgress>, u64) {
+    let mut stream = body.into_data_stream();
+    let mut transferred: u64 = 0;
+    let mut failure = None;
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                failure = Some(format!("Failed: upload interrupted: {}", e));
+                break;
+            }
+        };
+
+        transferred += chunk.len() as u64;
+        if transferred > settings.max_upload_size {
+            failure = Some(format!("Failed: upload exceeds the limit of {} bytes", settings.max_upload_size));
+            break;
+        }
+
+        // A write error means rclone exited (e.g. cancelled); its exit status tells why
+        if stdin.write_all(&chunk).await.is_err() {
+            break;
+        }
+        sync::set_job_transferred(&job_id, transferred).await;
+    }
+
+    if failure.is_some() {
+        let _ = child.start_kill();
+    }
+    drop(stdin);
+    let exit_status = child.wait().await;
+    let exit_code = exit_status.as_ref().ok().and_then(|status| status.code());
+
+    let status = match (failure, &exit_status) {
+        (Some(failure), _) => failure,
+        _ if sync::is_cancelling(&pool, &job_id).await => "Cancelled".to_string(),
+        (None, Ok(status)) if status.success() => "Completed".to_string(),
+        (None, Ok(_)) => "Failed".to_string(),
+        (None, Err(e)) => format!("Error: {}", e),
+    };
+
+    let progress = sync::finish_job(&pool, &sync_request, &job_id, &status, exit_code).await;
+    (status, progress, transferred)
+}
+
+fn download_error(status: StatusCode, message: &str) -> Response {
+    (status, ResponseJson(ApiResponse::<()>::error(message))).into_response()
+}
+
+/// Build a `remote:path` argument, rejecting remote names rclone would misinterpret
+pub(crate) fn remote_spec(remote: &str, path: &str) -> anyhow::Result<String> {
+    if remote.is_empty() || remote.contains(':') || remote.starts_with('-') {
+        return Err(anyhow::anyhow!("Invalid remote name '{}'", remote));
+    }
+    Ok(format!("{}:{}", remote, path))
+}
+
+/// Run an rclone command with the configured rclone.conf and return its output
+pub(crate) async fn run_rclone(settings: &Settings, command: &str, args: &[&str]) -> anyhow::Result<String> {
+    let config_path = settings.rclone_config_arg();
+
+    let output = Command::new(&settings.rclone_binary)
+        .args([command, "--config", &config_path])
+        .args(args)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!("rclone error: {}", error.trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
@@ -0,0 +1,148 @@
+use axum::{http::StatusCode, response::Json as ResponseJson, Extension};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tracing::warn;
+use uuid::Uuid;
+use crate::models::ApiResponse;
+use crate::settings::Settings;
+
+/// Oldest rclone release providing all flags and commands used by the server
+const MIN_RCLONE_VERSION: (u32, u32, u32) = (1, 58, 0);
+
+/// Time limit of a single readiness check
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthCheck {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+    pub duration_ms: u128,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub checks: Vec<HealthCheck>,
+}
+
+type HealthResponse<T> = (StatusCode, ResponseJson<ApiResponse<T>>);
+
+/// Liveness: the process is up and serving requests
+pub async fn healthz() -> HealthResponse<String> {
+    (StatusCode::OK, ResponseJson(ApiResponse::success("ok".to_string())))
+}
+
+/// Readiness: all dependencies needed to run jobs are available.
+/// Responds with 503 and the failed checks otherwise.
+pub async fn readyz(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Extension(settings): Extension<Arc<Settings>>,
+) -> HealthResponse<Readiness> {
+    let (database, rclone, config, log_dir) = tokio::join!(
+        run_check("database", check_database(&pool)),
+        run_check("rclone", check_rclone(&settings)),
+        run_check("rclone_config", check_rclone_config(&settings)),
+        run_check("log_dir", check_log_dir(&settings)),
+    );
+    let checks = vec![database, rclone, config, log_dir];
+    let ready = checks.iter().all(|check| check.ok);
+
+    let failed: Vec<&str> = checks.iter().filter(|c| !c.ok).map(|c| c.name.as_str()).collect();
+    if !ready {
+        warn!("🩺 Not ready, failed checks: {}", failed.join(", "));
+    }
+
+    let response = ApiResponse {
+        success: ready,
+        error: (!ready).then(|| format!("Failed checks: {}", failed.join(", "))),
+        data: Some(Readiness { ready, checks }),
+    };
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, ResponseJson(response))
+}
+
+async fn run_check(name: &str, check: impl std::future::Future<Output = Result<String, String>>) -> HealthCheck {
+    let started = Instant::now();
+    let result = match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("Timed out after {}s", CHECK_TIMEOUT.as_secs())),
+    };
+
+    let (ok, detail) = match result {
+        Ok(detail) => (true, detail),
+        Err(detail) => (false, detail),
+    };
+    HealthCheck {
+        name: name.to_string(),
+        ok,
+        detail,
+        duration_ms: started.elapsed().as_millis(),
+    }
+}
+
+async fn check_database(pool: &Pool<Sqlite>) -> Result<String, String> {
+    sqlx::query("SELECT 1")
+        .execute(pool)
+        .await
+        .map(|_| "SQLite answers".to_string())
+        .map_err(|e| format!("Query failed: {}", e))
+}
+
+async fn check_rclone(settings: &Settings) -> Result<String, String> {
+    let output = Command::new(&settings.rclone_binary)
+        .arg("version")
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("Could not run {}: {}", settings.rclone_binary, e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first_line = stdout.lines().next().unwrap_or("").trim();
+    let version = parse_rclone_version(first_line)
+        .ok_or_else(|| format!("Unexpected output of `{} version`: {}", settings.rclone_binary, first_line))?;
+
+    if version < MIN_RCLONE_VERSION {
+        let (major, minor, patch) = MIN_RCLONE_VERSION;
+        return Err(format!("{} is not supported, at least v{}.{}.{} is required", first_line, major, minor, patch));
+    }
+    Ok(first_line.to_string())
+}
+
+/// Parse `rclone v1.66.0` (also `v1.67.0-beta.7890...`) into its version numbers
+fn parse_rclone_version(line: &str) -> Option<(u32, u32, u32)> {
+    let version = line.strip_prefix("rclone v")?;
+    let mut numbers = version
+        .split(|c: char| !c.is_ascii_digit())
+        .take(3)
+        .map(|n| n.parse::<u32>().ok());
+    Some((numbers.next()??, numbers.next()??, numbers.next()??))
+}
+
+async fn check_rclone_config(settings: &Settings) -> Result<String, String> {
+    let path = &settings.rclone_config;
+    match tokio::fs::read(path).await {
+        Ok(content) => Ok(format!("{} is readable ({} bytes)", path.display(), content.len())),
+        // The config is created with the first remote
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(format!("{} does not exist yet", path.display())),
+        Err(e) => Err(format!("Cannot read {}: {}", path.display(), e)),
+    }
+}
+
+async fn check_log_dir(settings: &Settings) -> Result<String, String> {
+    let dir = &settings.log_dir;
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+
+    let probe = dir.join(format!(".readyz-{}", Uuid::new_v4()));
+    tokio::fs::write(&probe, b"ok")
+        .await
+        .map_err(|e| format!("Cannot write to {}: {}", dir.display(), e))?;
+    let _ = tokio::fs::remove_file(&probe).await;
+
+    Ok(format!("{} is writable", dir.display()))
+}
@@ -1,4 +1,10 @@
+pub mod bundle;
+pub mod compare;
 pub mod config;
 pub mod files;
+pub mod health;
+pub mod notifications;
+pub mod size;
+pub mod stats;
 pub mod sync;
 pub mod tasks;
\ No newline at end of file
@@ -0,0 +1,200 @@
+use axum::{extract::Json, extract::Path, response::Json as ResponseJson, Extension};
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+use tracing::{info, warn, error};
+use uuid::Uuid;
+use crate::database;
+use crate::models::{ApiResponse, NotificationChannel, NotificationChannelRequest, TaskNotification};
+use crate::notifications::{self, JobNotification};
+
+fn validate_channel(request: &NotificationChannelRequest) -> Result<(), String> {
+    if request.name.trim().is_empty() {
+        return Err("Channel name cannot be empty".to_string());
+    }
+    Ok(())
+}
+
+async fn find_channel(pool: &Pool<Sqlite>, channel_id: &str) -> Result<Option<NotificationChannel>, String> {
+    match database::get_all_channels(pool).await {
+        Ok(channels) => Ok(channels.into_iter().find(|c| c.id == channel_id)),
+        Err(e) => {
+            error!("Failed to retrieve notification channels: {}", e);
+            Err("Database error".to_string())
+        }
+    }
+}
+
+pub async fn list_channels(
+    Extension(pool): Extension<Pool<Sqlite>>,
+) -> ResponseJson<ApiResponse<Vec<NotificationChannel>>> {
+    match database::get_all_channels(&pool).await {
+        Ok(channels) => ResponseJson(ApiResponse::success(
+            channels
+                .into_iter()
+                .map(|channel| NotificationChannel { config: channel.config.redacted(), ..channel })
+                .collect(),
+        )),
+        Err(e) => {
+            error!("Failed to retrieve notification channels: {}", e);
+            ResponseJson(ApiResponse::error("Failed to retrieve notification channels"))
+        }
+    }
+}
+
+pub async fn create_channel(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Json(request): Json<NotificationChannelRequest>,
+) -> ResponseJson<ApiResponse<String>> {
+    info!("🔔 Creating notification channel: {}", request.name);
+
+    if let Err(e) = validate_channel(&request) {
+        return ResponseJson(ApiResponse::error(&e));
+    }
+
+    let channel = NotificationChannel {
+        id: Uuid::new_v4().to_string(),
+        name: request.name,
+        config: request.config,
+        enabled: request.enabled.unwrap_or(true),
+        created_at: Utc::now(),
+    };
+
+    match database::create_channel(&pool, &channel).await {
+        Ok(_) => {
+            info!("✅ Notification channel '{}' created with ID: {}", channel.name, channel.id);
+            ResponseJson(ApiResponse::success(channel.id))
+        }
+        Err(e) => {
+            error!("Failed to create notification channel '{}': {}", channel.name, e);
+            ResponseJson(ApiResponse::error("Failed to create notification channel, the name may already exist"))
+        }
+    }
+}
+
+pub async fn update_channel(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Path(channel_id): Path<String>,
+    Json(request): Json<NotificationChannelRequest>,
+) -> ResponseJson<ApiResponse<String>> {
+    info!("✏️ Updating notification channel: {}", channel_id);
+
+    if let Err(e) = validate_channel(&request) {
+        return ResponseJson(ApiResponse::error(&e));
+    }
+
+    let current = match find_channel(&pool, &channel_id).await {
+        Ok(Some(channel)) => channel,
+        Ok(None) => return ResponseJson(ApiResponse::error("Notification channel not found")),
+        Err(e) => return ResponseJson(ApiResponse::error(&e)),
+    };
+
+    // Secrets are listed as placeholders, sending one back keeps the stored secret
+    let mut config = request.config;
+    config.restore_secrets(&current.config);
+
+    let channel = NotificationChannel {
+        id: channel_id,
+        name: request.name,
+        config,
+        enabled: request.enabled.unwrap_or(current.enabled),
+        created_at: current.created_at,
+    };
+
+    match database::update_channel(&pool, &channel).await {
+        Ok(_) => {
+            info!("✅ Notification channel '{}' updated", channel.name);
+            ResponseJson(ApiResponse::success(channel.id))
+        }
+        Err(e) => {
+            error!("Failed to update notification channel {}: {}", channel.id, e);
+            ResponseJson(ApiResponse::error("Failed to update notification channel, the name may already exist"))
+        }
+    }
+}
+
+pub async fn delete_channel(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Path(channel_id): Path<String>,
+) -> ResponseJson<ApiResponse<String>> {
+    info!("🗑️ Deleting notification channel: {}", channel_id);
+
+    match database::delete_channel(&pool, &channel_id).await {
+        Ok(true) => ResponseJson(ApiResponse::success("Notification channel deleted successfully".to_string())),
+        Ok(false) => ResponseJson(ApiResponse::error("Notification channel not found")),
+        Err(e) => {
+            error!("Failed to delete notification channel {}: {}", channel_id, e);
+            ResponseJson(ApiResponse::error("Failed to delete notification channel"))
+        }
+    }
+}
+
+/// Send an example notification through a channel and report the delivery result
+pub async fn test_channel(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Path(channel_id): Path<String>,
+) -> ResponseJson<ApiResponse<String>> {
+    let channel = match find_channel(&pool, &channel_id).await {
+        Ok(Some(channel)) => channel,
+        Ok(None) => return ResponseJson(ApiResponse::error("Notification channel not found")),
+        Err(e) => return ResponseJson(ApiResponse::error(&e)),
+    };
+
+    info!("🔔 Sending test notification to '{}'", channel.name);
+    match notifications::send(&channel, &JobNotification::test()).await {
+        Ok(()) => ResponseJson(ApiResponse::success("Test notification sent".to_string())),
+        Err(e) => {
+            warn!("🔔 Test notification to '{}' failed: {}", channel.name, e);
+            ResponseJson(ApiResponse::error(&format!("Test notification failed: {}", e)))
+        }
+    }
+}
+
+pub async fn get_task_notifications(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Path(task_id): Path<String>,
+) -> ResponseJson<ApiResponse<Vec<TaskNotification>>> {
+    match database::get_task_notifications(&pool, &task_id).await {
+        Ok(notifications) => ResponseJson(ApiResponse::success(notifications)),
+        Err(e) => {
+            error!("Failed to retrieve notifications of task {}: {}", task_id, e);
+            ResponseJson(ApiResponse::error("Failed to retrieve task notifications"))
+        }
+    }
+}
+
+/// Replace the channel subscriptions of a task
+pub async fn set_task_notifications(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Path(task_id): Path<String>,
+    Json(notifications): Json<Vec<TaskNotification>>,
+) -> ResponseJson<ApiResponse<Vec<TaskNotification>>> {
+    info!("🔔 Updating notifications of task: {}", task_id);
+
+    match database::get_all_tasks(&pool).await {
+        Ok(tasks) if tasks.iter().any(|t| t.id == task_id) => {}
+        Ok(_) => return ResponseJson(ApiResponse::error("Task not found")),
+        Err(e) => {
+            error!("Failed to retrieve tasks: {}", e);
+            return ResponseJson(ApiResponse::error("Database error"));
+        }
+    }
+
+    let channels = match database::get_all_channels(&pool).await {
+        Ok(channels) => channels,
+        Err(e) => {
+            error!("Failed to retrieve notification channels: {}", e);
+            return ResponseJson(ApiResponse::error("Database error"));
+        }
+    };
+    if let Some(unknown) = notifications.iter().find(|n| !channels.iter().any(|c| c.id == n.channel_id)) {
+        return ResponseJson(ApiResponse::error(&format!("Notification channel {} not found", unknown.channel_id)));
+    }
+
+    match database::set_task_notifications(&pool, &task_id, &notifications).await {
+        Ok(()) => ResponseJson(ApiResponse::success(notifications)),
+        Err(e) => {
+            error!("Failed to update notifications of task {}: {}", task_id, e);
+            ResponseJson(ApiResponse::error("Failed to update task notifications"))
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use axum::{extract::Json, response::Json as ResponseJson, Extension};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use uuid::Uuid;
+use crate::handlers::files::remote_spec;
+use crate::models::{ApiResponse, DirSize, SizeRequest};
+use crate::settings::Settings;
+
+/// How long a finished calculation is reused for the same path
+const CACHE_TTL_SECS: i64 = 600;
+
+/// Number of walked entries after which a local calculation publishes its progress
+const PROGRESS_INTERVAL: u64 = 1000;
+
+struct SizeCalculation {
+    size: DirSize,
+    cancel: CancellationToken,
+}
+
+type SizeCalculations = Arc<Mutex<HashMap<String, SizeCalculation>>>;
+
+lazy_static::lazy_static! {
+    static ref SIZE_CALCULATIONS: SizeCalculations = Arc::new(Mutex::new(HashMap::new()));
+}
+
+fn is_running(size: &DirSize) -> bool {
+    size.status == "Running"
+}
+
+/// Start calculating the size of a directory, or return the running or cached calculation for it.
+/// Poll the result with `get_size_calculation`.
+pub async fn start_size_calculation(
+    Extension(settings): Extension<Arc<Settings>>,
+    Json(request): Json<SizeRequest>,
+) -> ResponseJson<ApiResponse<DirSize>> {
+    let target = match &request.remote {
+        Some(remote) => match remote_spec(remote, &request.path) {
+            Ok(target) => Some(target),
+            Err(e) => return ResponseJson(ApiResponse::error(&e.to_string())),
+        },
+        None if !Path::new(&request.path).is_dir() => {
+            return ResponseJson(ApiResponse::error("Directory does not exist"));
+        }
+        None => None,
+    };
+
+    let now = Utc::now().timestamp();
+    let mut calculations = SIZE_CALCULATIONS.lock().await;
+
+    // Forget expired results
+    calculations.retain(|_, c| c.size.finished_at.is_none_or(|finished| now - finished < CACHE_TTL_SECS));
+
+    let existing = calculations
+        .values()
+        .find(|c| c.size.remote == request.remote && c.size.path == request.path)
+        .map(|c| c.size.clone());
+    if let Some(existing) = existing {
+        if is_running(&existing) || (!request.refresh && existing.status == "Completed") {
+            return ResponseJson(ApiResponse::success(existing));
+        }
+        calculations.remove(&existing.id);
+    }
+
+    let size = DirSize {
+        id: Uuid::new_v4().to_string(),
+        remote: request.remote.clone(),
+        path: request.path.clone(),
+        status: "Running".to_string(),
+        bytes: 0,
+        files: 0,
+        dirs: None,
+        sizeless: None,
+        errors: None,
+        started_at: now,
+        finished_at: None,
+    };
+    let cancel = CancellationToken::new();
+    calculations.insert(size.id.clone(), SizeCalculation { size: size.clone(), cancel: cancel.clone() });
+    drop(calculations);
+
+    info!("📏 Calculating size of {}", target.as_deref().unwrap_or(&request.path));
+
+    let id = size.id.clone();
+    match target {
+        Some(target) => {
+            tokio::spawn(calculate_remote_size(id, target, settings, cancel));
+        }
+        None => {
+            let path = request.path.clone();
+            tokio::task::spawn_blocking(move || calculate_local_size(id, path, cancel));
+        }
+    }
+
+    ResponseJson(ApiResponse::success(size))
+}
+
+pub async fn get_size_calculation(id: String) -> ResponseJson<ApiResponse<DirSize>> {
+    match SIZE_CALCULATIONS.lock().await.get(&id) {
+        Some(calculation) => ResponseJson(ApiResponse::success(calculation.size.clone())),
+        None => ResponseJson(ApiResponse::error("Size calculation not found")),
+    }
+}
+
+pub async fn cancel_size_calculation(id: String) -> ResponseJson<ApiResponse<DirSize>> {
+    let mut calculations = SIZE_CALCULATIONS.lock().await;
+    let Some(calculation) = calculations.get_mut(&id) else {
+        return ResponseJson(ApiResponse::error("Size calculation not found"));
+    };
+
+    if !is_running(&calculation.size) {
+        return ResponseJson(ApiResponse::error("Size calculation is not running"));
+    }
+
+    info!("🛑 Cancelling size calculation of {}", calculation.size.path);
+    calculation.cancel.cancel();
+    finish(&mut calculation.size, "Cancelled");
+    ResponseJson(ApiResponse::success(calculation.size.clone()))
+}
+
+fn finish(size: &mut DirSize, status: &str) {
+    size.status = status.to_string();
+    size.finished_at = Some(Utc::now().timestamp());
+}
+
+/// Walk a local directory tree without following symlinks
+fn calculate_local_size(id: String, path: String, cancel: CancellationToken) {
+    let (mut bytes, mut files, mut dirs, mut errors) = (0u64, 0u64, 0u64, 0u64);
+    let mut walked = 0u64;
+
+    let publish = |bytes, files, dirs, errors, status: Option<&str>| {
+        let mut calculations = SIZE_CALCULATIONS.blocking_lock();
+        if let Some(calculation) = calculations.get_mut(&id) {
+            // A cancelled calculation keeps the numbers it had when it was cancelled
+            if !is_running(&calculation.size) {
+                return;
+            }
+            let size = &mut calculation.size;
+            size.bytes = bytes;
+            size.files = files;
+            size.dirs = Some(dirs);
+            size.errors = Some(errors);
+            if let Some(status) = status {
+                finish(size, status);
+            }
+        }
+    };
+
+    for entry in walkdir::WalkDir::new(&path).follow_links(false).min_depth(1) {
+        if cancel.is_cancelled() {
+            return;
+        }
+
+        match entry.and_then(|entry| entry.metadata().map(|metadata| (entry, metadata))) {
+            Ok((_, metadata)) if metadata.is_dir() => dirs += 1,
+            Ok((_, metadata)) => {
+                files += 1;
+                bytes += metadata.len();
+            }
+            Err(_) => errors += 1,
+        }
+
+        walked += 1;
+        if walked.is_multiple_of(PROGRESS_INTERVAL) {
+            publish(bytes, files, dirs, errors, None);
+        }
+    }
+
+    info!("📏 Size of {}: {} bytes in {} files", path, bytes, files);
+    publish(bytes, files, dirs, errors, Some("Completed"));
+}
+
+/// Run `rclone size --json`; the process is killed when the calculation is cancelled
+async fn calculate_remote_size(id: String, target: String, settings: Arc<Settings>, cancel: CancellationToken) {
+    let config_path = settings.rclone_config_arg();
+    let output = Command::new(&settings.rclone_binary)
+        .args(["size", "--json", "--config", &config_path, &target])
+        .kill_on_drop(true)
+        .output();
+
+    let result = tokio::select! {
+        output = output => output,
+        _ = cancel.cancelled() => {
+            info!("🛑 Size calculation of {} cancelled", target);
+            return;
+        }
+    };
+
+    let parsed = match result {
+        Ok(output) if output.status.success() => serde_json::from_slice::<serde_json::Value>(&output.stdout)
+            .map_err(|e| format!("Failed: unexpected rclone output: {}", e)),
+        Ok(output) => Err(format!("Failed: rclone error: {}", String::from_utf8_lossy(&output.stderr).trim())),
+        Err(e) => Err(format!("Failed: could not run rclone: {}", e)),
+    };
+
+    let mut calculations = SIZE_CALCULATIONS.lock().await;
+    let Some(calculation) = calculations.get_mut(&id) else {
+        return;
+    };
+    let size = &mut calculation.size;
+
+    match parsed {
+        Ok(json) => {
+            size.bytes = json["bytes"].as_u64().unwrap_or(0);
+            size.files = json["count"].as_u64().unwrap_or(0);
+            size.sizeless = json["sizeless"].as_u64();
+            info!("📏 Size of {}: {} bytes in {} files", target, size.bytes, size.files);
+            finish(size, "Completed");
+        }
+        Err(status) => {
+            warn!("📏 Size calculation of {}: {}", target, status);
+            finish(size, &status);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use axum::{extract::Query, response::Json as ResponseJson, Extension};
+use chrono::{Duration, Utc};
+use sqlx::{Pool, Sqlite};
+use tracing::error;
+use crate::database::{self, HistoryRow};
+use crate::models::{ApiResponse, StatsPoint, StatsQuery, StatsSeries, TransferHistory, TransferStats};
+
+/// History covered when `since` is not given
+const DEFAULT_HISTORY_DAYS: i64 = 30;
+
+/// Sums of finished jobs, turned into averages and rates once complete
+#[derive(Default)]
+struct Sums {
+    jobs: i64,
+    succeeded: i64,
+    failed: i64,
+    bytes: i64,
+    files: i64,
+    duration_secs: i64,
+    /// Bytes of the jobs with a run time of at least a second
+    timed_bytes: i64,
+}
+
+impl Sums {
+    fn add(&mut self, other: &Sums) {
+        self.jobs += other.jobs;
+        self.succeeded += other.succeeded;
+        self.failed += other.failed;
+        self.bytes += other.bytes;
+        self.files += other.files;
+        self.duration_secs += other.duration_secs;
+        self.timed_bytes += other.timed_bytes;
+    }
+
+    fn stats(&self) -> TransferStats {
+        let finished = self.succeeded + self.failed;
+        TransferStats {
+            jobs: self.jobs,
+            succeeded: self.succeeded,
+            failed: self.failed,
+            success_rate: (finished > 0).then(|| self.succeeded as f64 / finished as f64),
+            bytes: self.bytes,
+            files: self.files,
+            duration_secs: self.duration_secs,
+            avg_duration_secs: (self.jobs > 0).then(|| self.duration_secs as f64 / self.jobs as f64),
+            avg_throughput: (self.duration_secs > 0).then(|| self.timed_bytes as f64 / self.duration_secs as f64),
+        }
+    }
+}
+
+/// Transfer history of finished jobs as time series: bytes, files, success rate,
+/// durations and throughput per task or remote and day, week or month.
+/// Aggregated in SQL from the job records and the daily sums of jobs removed by the retention policy.
+pub async fn get_transfer_history(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Query(query): Query<StatsQuery>,
+) -> ResponseJson<ApiResponse<TransferHistory>> {
+    let until = query.until.unwrap_or_else(Utc::now);
+    let since = query.since.unwrap_or(until - Duration::days(DEFAULT_HISTORY_DAYS));
+    if since >= until {
+        return ResponseJson(ApiResponse::error("since must be before until"));
+    }
+
+    let rows = match database::get_transfer_history(
+        &pool,
+        query.group_by,
+        query.interval,
+        since.timestamp(),
+        until.timestamp(),
+        query.task.as_deref(),
+        query.remote.as_deref(),
+    )
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!("Failed to aggregate transfer history: {}", e);
+            return ResponseJson(ApiResponse::error("Failed to aggregate transfer history"));
+        }
+    };
+
+    ResponseJson(ApiResponse::success(TransferHistory {
+        group_by: query.group_by,
+        interval: query.interval,
+        since,
+        until,
+        series: build_series(rows),
+    }))
+}
+
+/// Split rows ordered by series and period into one series each
+fn build_series(rows: Vec<HistoryRow>) -> Vec<StatsSeries> {
+    let mut series: Vec<(StatsSeries, Sums)> = Vec::new();
+
+    for (key, period, jobs, succeeded, failed, bytes, files, duration_secs, timed_bytes) in rows {
+        let sums = Sums { jobs, succeeded, failed, bytes, files, duration_secs, timed_bytes };
+        if series.last().is_none_or(|(s, _)| s.key != key) {
+            series.push((StatsSeries { key, totals: TransferStats::default(), points: Vec::new() }, Sums::default()));
+        }
+
+        let (current, totals) = series.last_mut().expect("series was just added");
+        totals.add(&sums);
+        current.points.push(StatsPoint { period, stats: sums.stats() });
+    }
+
+    series
+        .into_iter()
+        .map(|(mut series, totals)| {
+            series.totals = totals.stats();
+            series
+        })
+        .collect()
+}
@@ -1,14 +1,23 @@
-use axum::{extract::Json, response::Json as ResponseJson};
+use axum::{extract::Json, response::Json as ResponseJson, Extension};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio::process::Command;
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 use chrono::{self, Utc};
 use tracing::{info, warn, error, debug};
 use serde_json;
-use crate::models::{ApiResponse, SyncRequest, SyncProgress};
+use crate::models::{ApiResponse, BwLimit, BwLimitRequest, JobAttempt, JobError, JobRecord, LogPage, LogQuery, SyncRequest, SyncProgress, Task};
+use crate::database;
+use crate::handlers::tasks;
+use crate::joblogs;
+use crate::metrics;
+use crate::notifications;
+use sqlx::{Pool, Sqlite};
+use crate::settings::{self, Settings};
 
 type SyncJobs = Arc<Mutex<HashMap<String, SyncProgress>>>;
 
@@ -16,12 +25,113 @@ lazy_static::lazy_static! {
     static ref SYNC_JOBS: SyncJobs = Arc::new(Mutex::new(HashMap::new()));
 }
 
+/// Status prefix of jobs whose rclone process could not be started
+pub const SPAWN_FAILED_STATUS: &str = "Failed to spawn rclone process";
+
+/// Status of jobs whose rclone process died with a previous server process
+pub const INTERRUPTED_STATUS: &str = "Interrupted";
+
+/// Status of interrupted jobs while the job continuing them is started
+const RESUMING_STATUS: &str = "Resuming";
+
+/// Status prefix of interrupted jobs that were resumed, followed by the new job's ID
+pub const RESUMED_STATUS: &str = "Resumed as job";
+
+/// Status of jobs waiting for the next attempt of their retry policy
+pub const RETRYING_STATUS: &str = "Retrying";
+
+/// How often a job waiting for its next attempt checks whether it was cancelled
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Whether a job has reached a final state
+pub fn is_finished(status: &str) -> bool {
+    status == "Completed"
+        || status == "Cancelled"
+        || status == INTERRUPTED_STATUS
+        || status.starts_with(RESUMED_STATUS)
+        || status.starts_with("Failed")
+        || status.starts_with("Error")
+}
+
+/// Whether a job failed because rclone could not be started at all
+pub fn is_spawn_failure(status: &str) -> bool {
+    status.starts_with(SPAWN_FAILED_STATUS)
+}
+
+/// User of the remote control server of rclone processes; the password is random per job
+const RC_USER: &str = "rclone-gui";
+
+/// Ports tried for the remote control server before a job runs without it
+const MAX_RC_PORT_ATTEMPTS: u32 = 3;
+
+/// Logged by rclone when its remote control server cannot listen, rclone exits right after
+const RC_START_FAILED: &str = "Failed to start remote control";
+
+/// Remote control server (`rclone --rc`) of a running job, used to change its bandwidth limit
+pub struct RemoteControl {
+    pub port: u16,
+    pub password: String,
+}
+
+impl RemoteControl {
+    /// Reserve a free local port for the remote control server of a new rclone process
+    fn new() -> std::io::Result<Self> {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+        Ok(Self { port, password: Uuid::new_v4().to_string() })
+    }
+}
+
+/// Build the persisted record of a job from its request and current progress
+fn job_record(sync_request: &SyncRequest, progress: &SyncProgress, pid: Option<u32>, rc: Option<&RemoteControl>) -> JobRecord {
+    JobRecord {
+        id: progress.id.clone(),
+        task_name: sync_request.task_name.clone(),
+        source_path: sync_request.source_path.clone(),
+        remote_name: sync_request.remote_name.clone(),
+        remote_path: sync_request.remote_path.clone(),
+        status: progress.status.clone(),
+        progress: progress.progress,
+        transferred: progress.transferred as i64,
+        total: progress.total as i64,
+        pid: pid.map(i64::from),
+        exit_code: progress.exit_code.map(i64::from),
+        start_time: progress.start_time,
+        end_time: progress.end_time,
+        files: progress.files as i64,
+        rc_port: rc.map(|rc| i64::from(rc.port)),
+        rc_password: rc.map(|rc| rc.password.clone()),
+        attempt: progress.attempt,
+    }
+}
+
+async fn persist_job(pool: &Pool<Sqlite>, record: JobRecord) {
+    if let Err(e) = database::save_job(pool, &record).await {
+        error!("Failed to persist job {}: {}", record.id, e);
+    }
+}
+
+/// Persist a running job without overwriting a cancellation requested meanwhile.
+/// Returns false if the job is being cancelled; its in-memory status is updated then.
+async fn persist_running_job(pool: &Pool<Sqlite>, progress: &mut SyncProgress, record: JobRecord) -> bool {
+    match database::update_job_unless_cancelling(pool, &record).await {
+        Ok(true) => true,
+        Ok(false) => {
+            progress.status = "Cancelling".to_string();
+            false
+        }
+        Err(e) => {
+            error!("Failed to persist job {}: {}", record.id, e);
+            true
+        }
+    }
+}
+
 /// Ensure the log directory exists and create a new log file with an initial entry
-async fn create_initial_log(job_id: &str, sync_request: &SyncRequest) -> tokio::io::Result<()> {
-    fs::create_dir_all("data/log").await?;
+async fn create_initial_log(settings: &Settings, job_id: &str, sync_request: &SyncRequest) -> tokio::io::Result<()> {
+    fs::create_dir_all(&settings.log_dir).await?;
 
     let remote_target = format!("{}:{}", sync_request.remote_name, sync_request.remote_path);
-    let log_file_path = format!("data/log/{}.log", job_id);
+    let log_file_path = settings.job_log_path(job_id);
     let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC");
 
     let initial_log = format!(
@@ -40,57 +150,137 @@ async fn create_initial_log(job_id: &str, sync_request: &SyncRequest) -> tokio::
     fs::write(&log_file_path, initial_log).await
 }
 
-pub async fn start_sync(Json(sync_request): Json<SyncRequest>) -> ResponseJson<ApiResponse<String>> {
-    let job_id = Uuid::new_v4().to_string();
+pub async fn start_sync(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Extension(settings): Extension<Arc<Settings>>,
+    Json(mut sync_request): Json<SyncRequest>,
+) -> ResponseJson<ApiResponse<String>> {
+    if let Some(Err(e)) = sync_request.bwlimit.as_deref().map(settings::parse_bwlimit) {
+        return ResponseJson(ApiResponse::error(&e));
+    }
+    sync_request.retry = match tasks::task_retry(sync_request.retry.take()) {
+        Ok(retry) => retry,
+        Err(e) => return ResponseJson(ApiResponse::error(&e)),
+    };
+
+    let job_id = register_job(&pool, &settings, &sync_request, "Starting", 0).await;
 
     info!("🚀 Starting new sync job: {}", job_id);
     info!("   Source: {}", sync_request.source_path);
     info!("   Remote: {}:{}", sync_request.remote_name, sync_request.remote_path);
 
-    let source_name = sync_request.source_path.split('/').last().unwrap_or(&sync_request.source_path).to_string();
+    let job_id_clone = job_id.clone();
+    let sync_jobs = SYNC_JOBS.clone();
+
+    tokio::spawn(async move {
+        execute_sync(job_id_clone, sync_request, sync_jobs, settings, pool).await;
+    });
+
+    ResponseJson(ApiResponse::success(job_id))
+}
+
+/// Add a new job to the job list, the database and its log file.
+/// Also used for operations not run by execute_sync, such as uploads.
+pub async fn register_job(pool: &Pool<Sqlite>, settings: &Settings, sync_request: &SyncRequest, status: &str, total: u64) -> String {
+    let job_id = Uuid::new_v4().to_string();
+
+    let source_name = sync_request.source_path.split('/').next_back().unwrap_or(&sync_request.source_path).to_string();
     let start_time = Utc::now().timestamp();
     
     let progress = SyncProgress {
         id: job_id.clone(),
         progress: 0.0,
-        status: "Starting".to_string(),
+        status: status.to_string(),
         transferred: 0,
-        total: 0,
+        total,
         source_name,
         start_time,
         end_time: None,
+        exit_code: None,
+        files: 0,
+        attempt: 1,
     };
 
+    persist_job(pool, job_record(sync_request, &progress, None, None)).await;
+
     {
         let mut jobs = SYNC_JOBS.lock().await;
         jobs.insert(job_id.clone(), progress);
     }
 
     // Immediately create the log file so it is visible in the UI
-    if let Err(e) = create_initial_log(&job_id, &sync_request).await {
+    if let Err(e) = create_initial_log(settings, &job_id, sync_request).await {
         error!("Failed to create initial log for {}: {}", job_id, e);
     } else {
         debug!("📝 Initial log file created for job {}", job_id);
     }
 
-    let job_id_clone = job_id.clone();
-    let sync_jobs = SYNC_JOBS.clone();
+    job_id
+}
 
-    tokio::spawn(async move {
-        execute_sync(job_id_clone, sync_request, sync_jobs).await;
-    });
+/// Drop a job from the jobs known to this process
+pub async fn forget_job(job_id: &str) {
+    SYNC_JOBS.lock().await.remove(job_id);
+}
 
-    ResponseJson(ApiResponse::success(job_id))
+/// Record the process of a registered job so it can be cancelled
+pub async fn set_job_pid(pool: &Pool<Sqlite>, sync_request: &SyncRequest, job_id: &str, pid: Option<u32>) {
+    let jobs = SYNC_JOBS.lock().await;
+    if let Some(progress) = jobs.get(job_id) {
+        persist_job(pool, job_record(sync_request, progress, pid, None)).await;
+    }
+}
+
+/// Update the transferred bytes of a registered job
+pub async fn set_job_transferred(job_id: &str, transferred: u64) {
+    let mut jobs = SYNC_JOBS.lock().await;
+    if let Some(progress) = jobs.get_mut(job_id) {
+        progress.transferred = transferred;
+        progress.total = progress.total.max(transferred);
+        progress.progress = if progress.total > 0 {
+            transferred as f64 / progress.total as f64 * 100.0
+        } else {
+            0.0
+        };
+    }
+}
+
+/// Whether cancellation of a job has been requested via cancel_sync_job
+pub async fn is_cancelling(pool: &Pool<Sqlite>, job_id: &str) -> bool {
+    matches!(
+        database::get_job(pool, job_id).await,
+        Ok(Some(ref job)) if job.status == "Cancelling"
+    )
 }
 
-pub async fn get_sync_progress(job_id: String) -> ResponseJson<ApiResponse<SyncProgress>> {
+/// Set the final status of a registered job and persist it
+pub async fn finish_job(pool: &Pool<Sqlite>, sync_request: &SyncRequest, job_id: &str, status: &str, exit_code: Option<i32>) -> Option<SyncProgress> {
+    let mut jobs = SYNC_JOBS.lock().await;
+    let progress = jobs.get_mut(job_id)?;
+
+    progress.status = status.to_string();
+    progress.end_time = Some(Utc::now().timestamp());
+    progress.exit_code = exit_code;
+    if status == "Completed" {
+        progress.progress = 100.0;
+    }
+
+    persist_job(pool, job_record(sync_request, progress, None, None)).await;
+    metrics::record_job_finished(sync_request, progress);
+    Some(progress.clone())
+}
+
+pub async fn get_sync_progress(
+    Extension(settings): Extension<Arc<Settings>>,
+    job_id: String,
+) -> ResponseJson<ApiResponse<SyncProgress>> {
     let mut jobs = SYNC_JOBS.lock().await;
 
     match jobs.get_mut(&job_id) {
         Some(progress) => {
             // Update progress from log file if job is running
             if progress.status == "Running" {
-                if let Some((percent, transferred, total)) = parse_latest_progress_from_log(&job_id).await {
+                if let Some((percent, transferred, total)) = parse_latest_progress_from_log(&settings, &job_id).await {
                     progress.progress = percent;
                     progress.transferred = transferred;
                     progress.total = total;
@@ -103,37 +293,7 @@ pub async fn get_sync_progress(job_id: String) -> ResponseJson<ApiResponse<SyncP
 }
 
 pub async fn list_sync_jobs() -> ResponseJson<ApiResponse<Vec<SyncProgress>>> {
-    let mut jobs = SYNC_JOBS.lock().await;
-    
-    // Clean up jobs older than 24 hours (86400 seconds)
-    let now = Utc::now().timestamp();
-    let cleanup_threshold = now - 86400; // 24 hours ago
-    
-    let mut jobs_to_remove = Vec::new();
-    
-    // Find completed jobs older than 24 hours
-    for (job_id, job) in jobs.iter() {
-        if let Some(end_time) = job.end_time {
-            if end_time < cleanup_threshold && (job.status == "Completed" || job.status == "Failed" || job.status.contains("Error")) {
-                jobs_to_remove.push(job_id.clone());
-            }
-        }
-    }
-    
-    // Remove old jobs and their log files
-    for job_id in jobs_to_remove {
-        if let Some(job) = jobs.remove(&job_id) {
-            info!("🧹 Auto-cleanup: Removing job {} (completed {}h ago)", 
-                job_id, (now - job.end_time.unwrap_or(now)) / 3600);
-            
-            // Remove log file
-            let log_file_path = format!("data/log/{}.log", job_id);
-            if let Err(e) = tokio::fs::remove_file(&log_file_path).await {
-                debug!("⚠️ Could not delete log file {}: {}", log_file_path, e);
-            }
-        }
-    }
-
+    let jobs = SYNC_JOBS.lock().await;
     let mut job_list: Vec<SyncProgress> = jobs.values().cloned().collect();
 
     // Sort by creation time (newest first) - using job_id as timestamp proxy
@@ -142,11 +302,39 @@ pub async fn list_sync_jobs() -> ResponseJson<ApiResponse<Vec<SyncProgress>>> {
     ResponseJson(ApiResponse::success(job_list))
 }
 
-pub async fn get_sync_log(job_id: String) -> ResponseJson<ApiResponse<String>> {
-    let log_file_path = format!("data/log/{}.log", job_id);
-    debug!("📖 Reading log file for job {}: {}", job_id, log_file_path);
+/// Jobs of all rclone-gui processes sharing the database, newest first.
+/// Jobs running in this process report their live progress.
+pub async fn list_job_records(
+    Extension(pool): Extension<Pool<Sqlite>>,
+) -> ResponseJson<ApiResponse<Vec<JobRecord>>> {
+    let mut records = match database::get_all_jobs(&pool).await {
+        Ok(records) => records,
+        Err(e) => {
+            error!("Failed to retrieve jobs: {}", e);
+            return ResponseJson(ApiResponse::error("Failed to retrieve jobs"));
+        }
+    };
+
+    let jobs = SYNC_JOBS.lock().await;
+    for record in records.iter_mut() {
+        if let Some(progress) = jobs.get(&record.id) {
+            record.progress = progress.progress;
+            record.transferred = progress.transferred as i64;
+            record.total = progress.total as i64;
+        }
+    }
+
+    ResponseJson(ApiResponse::success(records))
+}
+
+pub async fn get_sync_log(
+    Extension(settings): Extension<Arc<Settings>>,
+    job_id: String,
+) -> ResponseJson<ApiResponse<String>> {
+    let log_file_path = settings.job_log_path(&job_id);
+    debug!("📖 Reading log file for job {}: {}", job_id, log_file_path.display());
 
-    match fs::read_to_string(&log_file_path).await {
+    match joblogs::read(&settings, &job_id).await {
         Ok(content) => {
             info!("📖 Log file read successfully for job {}, {} bytes", job_id, content.len());
             ResponseJson(ApiResponse::success(content))
@@ -158,15 +346,360 @@ pub async fn get_sync_log(job_id: String) -> ResponseJson<ApiResponse<String>> {
     }
 }
 
-pub async fn delete_sync_job(job_id: String) -> ResponseJson<ApiResponse<String>> {
+/// Parsed log entries of a job, filtered by level, object and time and paged with `offset`/`limit` or `tail`.
+/// rclone's periodic stats entries are left out unless `stats` is set.
+pub async fn get_sync_log_entries(
+    Extension(settings): Extension<Arc<Settings>>,
+    job_id: String,
+    query: LogQuery,
+) -> ResponseJson<ApiResponse<LogPage>> {
+    match joblogs::query(&settings, &job_id, query).await {
+        Ok(page) => ResponseJson(ApiResponse::success(page)),
+        Err(e) => {
+            warn!("📖 Log query failed for job {}: {}", job_id, e);
+            ResponseJson(ApiResponse::error(&format!("Log file not found: {}", e)))
+        }
+    }
+}
+
+/// Read or change the bandwidth limit of a running job through its rclone remote control server
+async fn remote_bwlimit(pool: &Pool<Sqlite>, job_id: &str, rate: Option<&str>) -> Result<BwLimit, String> {
+    let job = match database::get_job(pool, job_id).await {
+        Ok(Some(job)) => job,
+        Ok(None) => return Err("Job not found".to_string()),
+        Err(e) => {
+            error!("Failed to retrieve job {}: {}", job_id, e);
+            return Err("Failed to retrieve job".to_string());
+        }
+    };
+    if job.status != "Running" {
+        return Err("Job is not running".to_string());
+    }
+    let (Some(port), Some(password)) = (job.rc_port, job.rc_password) else {
+        return Err("Job was started without remote control, its bandwidth limit cannot be changed".to_string());
+    };
+
+    let body = match rate {
+        Some(rate) => serde_json::json!({ "rate": rate }),
+        None => serde_json::json!({}),
+    };
+    let response = reqwest::Client::new()
+        .post(format!("http://127.0.0.1:{}/core/bwlimit", port))
+        .basic_auth(RC_USER, Some(password))
+        .json(&body)
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("rclone remote control failed: {}", e))?;
+
+    response
+        .json::<BwLimit>()
+        .await
+        .map_err(|e| format!("Unexpected response from rclone remote control: {}", e))
+}
+
+/// Current bandwidth limit of a running job
+pub async fn get_job_bwlimit(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    job_id: String,
+) -> ResponseJson<ApiResponse<BwLimit>> {
+    match remote_bwlimit(&pool, &job_id, None).await {
+        Ok(limit) => ResponseJson(ApiResponse::success(limit)),
+        Err(e) => ResponseJson(ApiResponse::error(&e)),
+    }
+}
+
+/// Change the bandwidth limit of a running job. The new rate replaces a timetable for the rest of the job.
+pub async fn set_job_bwlimit(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    job_id: String,
+    request: BwLimitRequest,
+) -> ResponseJson<ApiResponse<BwLimit>> {
+    if let Err(e) = settings::parse_bwlimit_rate(&request.rate) {
+        return ResponseJson(ApiResponse::error(&e));
+    }
+
+    info!("🐢 Bandwidth limit of job {} set to {}", job_id, request.rate);
+    match remote_bwlimit(&pool, &job_id, Some(&request.rate)).await {
+        Ok(limit) => ResponseJson(ApiResponse::success(limit)),
+        Err(e) => {
+            warn!("Could not change the bandwidth limit of job {}: {}", job_id, e);
+            ResponseJson(ApiResponse::error(&e))
+        }
+    }
+}
+
+/// Files a finished job failed to transfer, with the last error and retry count of each
+pub async fn get_sync_errors(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    job_id: String,
+) -> ResponseJson<ApiResponse<Vec<JobError>>> {
+    match database::get_job(&pool, &job_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return ResponseJson(ApiResponse::error("Job not found")),
+        Err(e) => {
+            error!("Failed to retrieve job {}: {}", job_id, e);
+            return ResponseJson(ApiResponse::error("Failed to retrieve job"));
+        }
+    }
+
+    match database::get_job_errors(&pool, &job_id).await {
+        Ok(errors) => ResponseJson(ApiResponse::success(errors)),
+        Err(e) => {
+            error!("Failed to retrieve errors of job {}: {}", job_id, e);
+            ResponseJson(ApiResponse::error("Failed to retrieve job errors"))
+        }
+    }
+}
+
+/// Runs of rclone of a job, more than one if its task's retry policy started it again
+pub async fn get_sync_attempts(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    job_id: String,
+) -> ResponseJson<ApiResponse<Vec<JobAttempt>>> {
+    match database::get_job(&pool, &job_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return ResponseJson(ApiResponse::error("Job not found")),
+        Err(e) => {
+            error!("Failed to retrieve job {}: {}", job_id, e);
+            return ResponseJson(ApiResponse::error("Failed to retrieve job"));
+        }
+    }
+
+    match database::get_job_attempts(&pool, &job_id).await {
+        Ok(attempts) => ResponseJson(ApiResponse::success(attempts)),
+        Err(e) => {
+            error!("Failed to retrieve attempts of job {}: {}", job_id, e);
+            ResponseJson(ApiResponse::error("Failed to retrieve job attempts"))
+        }
+    }
+}
+
+/// Start a new job copying only the files a finished job failed to transfer
+pub async fn retry_failed_files(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Extension(settings): Extension<Arc<Settings>>,
+    job_id: String,
+) -> ResponseJson<ApiResponse<String>> {
+    info!("🔁 Retry request for failed files of job {}", job_id);
+
+    let job = match database::get_job(&pool, &job_id).await {
+        Ok(Some(job)) => job,
+        Ok(None) => return ResponseJson(ApiResponse::error("Job not found")),
+        Err(e) => {
+            error!("Failed to retrieve job {}: {}", job_id, e);
+            return ResponseJson(ApiResponse::error("Failed to retrieve job"));
+        }
+    };
+    if !is_finished(&job.status) {
+        return ResponseJson(ApiResponse::error("Job is still running"));
+    }
+    if job.source_path.starts_with("upload://") {
+        return ResponseJson(ApiResponse::error("Uploads cannot be retried, upload the file again"));
+    }
+
+    let errors = match database::get_job_errors(&pool, &job_id).await {
+        Ok(errors) if errors.is_empty() => return ResponseJson(ApiResponse::error("Job has no failed files")),
+        Ok(errors) => errors,
+        Err(e) => {
+            error!("Failed to retrieve errors of job {}: {}", job_id, e);
+            return ResponseJson(ApiResponse::error("Failed to retrieve job errors"));
+        }
+    };
+
+    let task = job_task(&pool, &job).await;
+    let sync_request = SyncRequest {
+        files_from: Some(errors.into_iter().map(|e| e.object).collect()),
+        ..repeat_request(&job, task.as_ref())
+    };
+
+    start_sync(Extension(pool), Extension(settings), Json(sync_request)).await
+}
+
+/// Task a persisted job was started from, if it still exists
+async fn job_task(pool: &Pool<Sqlite>, job: &JobRecord) -> Option<Task> {
+    match &job.task_name {
+        Some(name) => database::get_task_by_name(pool, name).await.ok().flatten(),
+        None => None,
+    }
+}
+
+/// Request running a persisted job again. Chunk settings and bandwidth limits are
+/// not stored with the job, its task's current ones are used.
+fn repeat_request(job: &JobRecord, task: Option<&Task>) -> SyncRequest {
+    SyncRequest {
+        source_path: job.source_path.clone(),
+        remote_name: job.remote_name.clone(),
+        remote_path: job.remote_path.clone(),
+        chunk_size: task.and_then(|t| t.chunk_size.clone()),
+        use_chunking: task.map(|t| t.use_chunking),
+        task_name: job.task_name.clone(),
+        files_from: None,
+        bwlimit: task.and_then(|t| t.bwlimit.clone()),
+        retry: task.and_then(|t| t.retry.clone()),
+    }
+}
+
+/// Start an interrupted job again. rclone skips the files already copied,
+/// so the new job continues where the interrupted one stopped.
+pub async fn resume_job(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Extension(settings): Extension<Arc<Settings>>,
+    job_id: String,
+) -> ResponseJson<ApiResponse<String>> {
+    info!("▶️ Resume request for job {}", job_id);
+
+    let job = match database::get_job(&pool, &job_id).await {
+        Ok(Some(job)) => job,
+        Ok(None) => return ResponseJson(ApiResponse::error("Job not found")),
+        Err(e) => {
+            error!("Failed to retrieve job {}: {}", job_id, e);
+            return ResponseJson(ApiResponse::error("Failed to retrieve job"));
+        }
+    };
+    if job.status.starts_with(RESUMED_STATUS) || job.status == RESUMING_STATUS {
+        return ResponseJson(ApiResponse::error("Job was already resumed"));
+    }
+    if job.status != INTERRUPTED_STATUS {
+        return ResponseJson(ApiResponse::error("Only interrupted jobs can be resumed"));
+    }
+    if job.source_path.starts_with("upload://") {
+        return ResponseJson(ApiResponse::error("Uploads cannot be resumed, upload the file again"));
+    }
+
+    let task = job_task(&pool, &job).await;
+    resume(&pool, &settings, &job, task.as_ref()).await
+}
+
+/// Start a new job continuing an interrupted one. The interrupted job is claimed first,
+/// so concurrent requests cannot resume it twice, and then points to the new job.
+async fn resume(pool: &Pool<Sqlite>, settings: &Arc<Settings>, job: &JobRecord, task: Option<&Task>) -> ResponseJson<ApiResponse<String>> {
+    match database::replace_job_status(pool, &job.id, INTERRUPTED_STATUS, RESUMING_STATUS).await {
+        Ok(true) => {}
+        Ok(false) => return ResponseJson(ApiResponse::error("Job was already resumed")),
+        Err(e) => {
+            error!("Failed to mark job {} as resuming: {}", job.id, e);
+            return ResponseJson(ApiResponse::error("Failed to resume job"));
+        }
+    }
+
+    // A job retrying failed files only resumes with these files
+    let files_from = match fs::read_to_string(settings.job_files_from_path(&job.id)).await {
+        Ok(files) => Some(files.lines().filter(|l| !l.is_empty()).map(String::from).collect()),
+        Err(_) => None,
+    };
+    let sync_request = SyncRequest { files_from, ..repeat_request(job, task) };
+
+    let response = start_sync(Extension(pool.clone()), Extension(settings.clone()), Json(sync_request)).await;
+    let status = match &response.0.data {
+        Some(new_id) => {
+            info!("▶️ Job {} resumed as job {}", job.id, new_id);
+            let status = format!("{} {}", RESUMED_STATUS, new_id);
+            append_log_line(settings, &job.id, &status).await;
+            status
+        }
+        None => INTERRUPTED_STATUS.to_string(),
+    };
+    if let Err(e) = database::set_job_status(pool, &job.id, &status).await {
+        error!("Failed to update status of resumed job {}: {}", job.id, e);
+    }
+    response
+}
+
+/// Append a line to the plain log of a job, the same way the initial log is written
+async fn append_log_line(settings: &Settings, job_id: &str, message: &str) {
+    let line = format!("[{}] {}\n", Utc::now().format("%Y-%m-%d %H:%M:%S UTC"), message);
+    let result = async {
+        let mut file = fs::OpenOptions::new().append(true).open(settings.job_log_path(job_id)).await?;
+        file.write_all(line.as_bytes()).await
+    }
+    .await;
+    if let Err(e) = result {
+        warn!("Could not write to the log of job {}: {}", job_id, e);
+    }
+}
+
+/// Finish the jobs whose rclone process died with a previous server process, e.g. when
+/// the container was restarted. They are marked "Interrupted" and restarted if their task
+/// asks for it. Jobs whose rclone process is still alive belong to other processes such
+/// as `task run` and are left alone.
+pub async fn recover_interrupted_jobs(pool: &Pool<Sqlite>, settings: &Arc<Settings>) {
+    let jobs = match database::get_all_jobs(pool).await {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            error!("Failed to look for interrupted jobs: {}", e);
+            return;
+        }
+    };
+
+    for mut job in jobs.into_iter().filter(|job| !is_finished(&job.status)) {
+        if job.pid.is_some_and(|pid| job_process_alive(pid, &settings.job_log_path(&job.id))) {
+            debug!("Job {} is still running in another process", job.id);
+            continue;
+        }
+
+        let cancelled = job.status == "Cancelling";
+        job.status = if cancelled { "Cancelled" } else { INTERRUPTED_STATUS }.to_string();
+        job.end_time = Some(Utc::now().timestamp());
+        job.pid = None;
+        job.rc_port = None;
+        job.rc_password = None;
+        save_failed_objects(pool, settings, &job.id).await;
+        if let Ok(Some(stats)) = joblogs::last_stats(settings, &job.id).await {
+            job.files = stats["transfers"].as_i64().unwrap_or(job.files);
+            job.transferred = stats["bytes"].as_i64().unwrap_or(job.transferred);
+        }
+        let reason = format!("{}, rclone-gui was restarted while it ran", job.status.to_lowercase());
+        append_log_line(settings, &job.id, &format!("Job {}", reason)).await;
+        persist_job(pool, job.clone()).await;
+        warn!("⚠️ Job {} {}", job.id, reason);
+
+        if cancelled {
+            continue;
+        }
+        let task = job_task(pool, &job).await;
+        if task.as_ref().is_some_and(|t| t.resume_interrupted) && !job.source_path.starts_with("upload://") {
+            if let Some(e) = resume(pool, settings, &job, task.as_ref()).await.0.error {
+                error!("Failed to resume job {}: {}", job.id, e);
+            }
+        } else {
+            let progress = SyncProgress {
+                id: job.id.clone(),
+                progress: job.progress,
+                status: job.status.clone(),
+                transferred: job.transferred as u64,
+                total: job.total as u64,
+                source_name: String::new(),
+                start_time: job.start_time,
+                end_time: job.end_time,
+                exit_code: None,
+                files: job.files as u64,
+                attempt: job.attempt,
+            };
+            notifications::job_finished(pool, settings, &repeat_request(&job, task.as_ref()), &progress);
+        }
+    }
+}
+
+pub async fn delete_sync_job(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Extension(settings): Extension<Arc<Settings>>,
+    job_id: String,
+) -> ResponseJson<ApiResponse<String>> {
     info!("🗑️ Delete request for job {}", job_id);
 
     let mut jobs = SYNC_JOBS.lock().await;
 
-    // Check if job exists and is completed
-    let can_delete = if let Some(job) = jobs.get(&job_id) {
-        let deletable = job.status == "Completed" || job.status == "Failed" || job.status.contains("Error");
-        info!("📊 Job {} status: {}, can delete: {}", job_id, job.status, deletable);
+    // Check if job exists and is completed (jobs of other processes are only known from the database)
+    let status = match jobs.get(&job_id) {
+        Some(job) => Some(job.status.clone()),
+        None => database::get_job(&pool, &job_id).await.ok().flatten().map(|job| job.status),
+    };
+
+    let can_delete = if let Some(status) = status {
+        let deletable = is_finished(&status);
+        info!("📊 Job {} status: {}, can delete: {}", job_id, status, deletable);
         deletable
     } else {
         warn!("❌ Job {} not found for deletion", job_id);
@@ -177,26 +710,132 @@ pub async fn delete_sync_job(job_id: String) -> ResponseJson<ApiResponse<String>
         return ResponseJson(ApiResponse::error("Can only delete completed or failed jobs"));
     }
 
-    // Remove from memory
+    // Remove from memory and database
     jobs.remove(&job_id);
+    if let Err(e) = database::delete_job(&pool, &job_id).await {
+        warn!("Could not delete job record {}: {}", job_id, e);
+    }
 
     // Remove log file
-    let log_file_path = format!("data/log/{}.log", job_id);
-    if let Err(e) = fs::remove_file(&log_file_path).await {
-        println!("Warning: Could not delete log file {}: {}", log_file_path, e);
+    if let Err(e) = joblogs::remove(&settings, &job_id).await {
+        warn!("Could not delete log file of job {}: {}", job_id, e);
     }
 
     ResponseJson(ApiResponse::success("Job deleted successfully".to_string()))
 }
 
-async fn execute_sync(job_id: String, sync_request: SyncRequest, sync_jobs: SyncJobs) {
+/// Cancel a running job by terminating its rclone process.
+/// Works for jobs started by this process as well as by other rclone-gui processes sharing the database.
+pub async fn cancel_sync_job(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Extension(settings): Extension<Arc<Settings>>,
+    job_id: String,
+) -> ResponseJson<ApiResponse<String>> {
+    info!("🛑 Cancel request for job {}", job_id);
+
+    let job = match database::get_job(&pool, &job_id).await {
+        Ok(Some(job)) => job,
+        Ok(None) => return ResponseJson(ApiResponse::error("Job not found")),
+        Err(e) => {
+            error!("Failed to retrieve job {}: {}", job_id, e);
+            return ResponseJson(ApiResponse::error("Failed to retrieve job"));
+        }
+    };
+
+    if is_finished(&job.status) {
+        return ResponseJson(ApiResponse::error("Job is not running"));
+    }
+
+    if job.pid.is_none() && job.status != RETRYING_STATUS {
+        return ResponseJson(ApiResponse::error("Job has no running rclone process yet"));
+    }
+
+    // The pid of a process that died without updating the job may have been reused
+    if job.pid.is_some_and(|pid| !job_process_alive(pid, &settings.job_log_path(&job_id))) {
+        warn!("❌ rclone process of job {} is no longer running", job_id);
+        return ResponseJson(ApiResponse::error("The rclone process of the job is no longer running"));
+    }
+
+    // Mark the job first so execute_sync reports it as cancelled and not as failed
+    if let Err(e) = database::set_job_status(&pool, &job_id, "Cancelling").await {
+        error!("Failed to mark job {} as cancelling: {}", job_id, e);
+        return ResponseJson(ApiResponse::error("Failed to cancel job"));
+    }
+    if let Some(progress) = SYNC_JOBS.lock().await.get_mut(&job_id) {
+        progress.status = "Cancelling".to_string();
+    }
+
+    // Between two attempts there is no process, the job stops instead of retrying
+    let Some(pid) = job.pid else {
+        return ResponseJson(ApiResponse::success("Job cancellation requested".to_string()));
+    };
+    match terminate_process(pid) {
+        Ok(_) => {
+            info!("✅ Sent termination signal to rclone process {} of job {}", pid, job_id);
+            ResponseJson(ApiResponse::success("Job cancellation requested".to_string()))
+        }
+        Err(e) => {
+            error!("Failed to terminate rclone process {} of job {}: {}", pid, job_id, e);
+            ResponseJson(ApiResponse::error(&format!("Failed to terminate rclone process: {}", e)))
+        }
+    }
+}
+
+#[cfg(unix)]
+fn terminate_process(pid: i64) -> std::io::Result<()> {
+    // SAFETY: kill() has no memory safety requirements
+    let result = unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// Whether the rclone process of a job is still running, also if it belongs to another user.
+/// The pid is only trusted if the process writes to the job's log file, as pids are reused
+/// once the original process is gone.
+#[cfg(unix)]
+fn job_process_alive(pid: i64, log_file: &std::path::Path) -> bool {
+    if i64::from(std::process::id()) == pid {
+        return false;
+    }
+    // Zombies, e.g. the rclone processes of a killed server, have an empty command line
+    if let Ok(cmdline) = std::fs::read(format!("/proc/{}/cmdline", pid)) {
+        let log_file = log_file.to_string_lossy();
+        let args: Vec<String> = cmdline
+            .split(|&b| b == 0)
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect();
+        return args.windows(2).any(|pair| pair[0] == "--log-file" && pair[1] == log_file)
+            || args.iter().any(|arg| arg.strip_prefix("--log-file=") == Some(&*log_file));
+    }
+    // Without procfs the command line cannot be checked, only whether the pid exists
+    // SAFETY: kill() with signal 0 only checks the process, it has no memory safety requirements
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn job_process_alive(_pid: i64, _log_file: &std::path::Path) -> bool {
+    false
+}
+
+#[cfg(not(unix))]
+fn terminate_process(_pid: i64) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "Cancelling jobs is only supported on unix"))
+}
+
+/// Run the rclone process of a job. Everything logged while the job runs carries its `job_id` and `task_name`.
+#[tracing::instrument(name = "job", skip_all, fields(job_id = %job_id, task_name = sync_request.task_name.as_deref().unwrap_or_default()))]
+async fn execute_sync(job_id: String, sync_request: SyncRequest, sync_jobs: SyncJobs, settings: Arc<Settings>, pool: Pool<Sqlite>) {
     let remote_target = format!("{}:{}", sync_request.remote_name, sync_request.remote_path);
-    let config_path = "data/cfg/rclone.conf";
-    let log_file_path = format!("data/log/{}.log", job_id);
+    let config_path = settings.rclone_config_arg();
+    let log_file_path = settings.job_log_path(&job_id).to_string_lossy().to_string();
 
     // Ensure log directory and initial log exist in case start_sync didn't manage to create them (e.g. on crash)
-    if let Err(e) = create_initial_log(&job_id, &sync_request).await {
-        eprintln!("Failed to ensure initial log: {}", e);
+    if let Err(e) = create_initial_log(&settings, &job_id, &sync_request).await {
+        error!("Failed to ensure initial log: {}", e);
     }
 
     {
@@ -209,7 +848,7 @@ async fn execute_sync(job_id: String, sync_request: SyncRequest, sync_jobs: Sync
     // Build basic rclone arguments with JSON logging
     let mut args = vec![
         "copy",
-        "--config", config_path,
+        "--config", &config_path,
         &sync_request.source_path,
         &remote_target,
         "--stats", "1s",
@@ -227,6 +866,21 @@ async fn execute_sync(job_id: String, sync_request: SyncRequest, sync_jobs: Sync
         "--log-level", "INFO",
     ];
 
+    // Limit the job to the listed paths, e.g. when retrying failed files
+    let files_from_path = settings.job_files_from_path(&job_id).to_string_lossy().to_string();
+    if let Some(files) = &sync_request.files_from {
+        if let Err(e) = fs::write(&files_from_path, files.join("\n") + "\n").await {
+            error!("❌ Could not write file list for job {}: {}", job_id, e);
+            let status = format!("Error: could not write file list: {}", e);
+            if let Some(progress) = finish_job(&pool, &sync_request, &job_id, &status, None).await {
+                notifications::job_finished(&pool, &settings, &sync_request, &progress);
+            }
+            return;
+        }
+        info!("📄 Limiting job {} to {} listed files", job_id, files.len());
+        args.extend(["--files-from", &files_from_path]);
+    }
+
     // Add multi-threading and WebDAV chunk size based on chunk size selection
     let multi_thread_streams_str;
     let multi_thread_cutoff_str;
@@ -277,32 +931,155 @@ async fn execute_sync(job_id: String, sync_request: SyncRequest, sync_jobs: Sync
         info!("🔧 Using default settings (streams: 4, cutoff: 250M, webdav-chunk: 50M)");
     }
 
-    // Print the full rclone command being executed (debug only)
-    info!("🚀 Executing rclone command: {}", args.join(" "));
-    
-    // Spawn rclone - no need to capture output since it writes to log file
-    let mut child = match Command::new("rclone")
-        .args(&args)
-        .spawn()
-    {
-        Ok(child) => {
-            info!("✅ Rclone process started for job {}", job_id);
-            child
-        },
-        Err(e) => {
-            let error_msg = format!("Failed to spawn rclone process: {}", e);
-            error!("❌ {}", error_msg);
-            
+    // A task's own bandwidth limit replaces the global one
+    let bwlimit = sync_request.bwlimit.as_ref().or(settings.bwlimit.as_ref());
+    if let Some(bwlimit) = bwlimit {
+        info!("🐢 Bandwidth limit: {}", bwlimit);
+        args.extend(["--bwlimit", bwlimit]);
+    }
+
+    let mut attempt = 1;
+    let mut attempt_start = Utc::now().timestamp();
+    let mut rc_failures = 0;
+    let status = loop {
+        let mut attempt_args = args.clone();
+
+        // Remote control server, to change the bandwidth limit while the job runs
+        let rc = if rc_failures < MAX_RC_PORT_ATTEMPTS {
+            match RemoteControl::new() {
+                Ok(rc) => Some(rc),
+                Err(e) => {
+                    warn!("No free port for the remote control of job {}, its bandwidth limit cannot be changed: {}", job_id, e);
+                    None
+                }
+            }
+        } else {
+            warn!("Running job {} without remote control, its bandwidth limit cannot be changed", job_id);
+            None
+        };
+        let rc_addr = rc.as_ref().map(|rc| format!("127.0.0.1:{}", rc.port));
+        if let Some(rc_addr) = &rc_addr {
+            attempt_args.extend(["--rc", "--rc-addr", rc_addr, "--rc-user", RC_USER]);
+        }
+
+        // Print the full rclone command being executed (debug only)
+        info!("🚀 Executing rclone command: {}", attempt_args.join(" "));
+
+        // Spawn rclone - no need to capture output since it writes to log file
+        let mut command = Command::new(&settings.rclone_binary);
+        command.args(&attempt_args);
+        if let Some(rc) = &rc {
+            // Passed through the environment to keep it out of the process list
+            command.env("RCLONE_RC_PASS", &rc.password);
+        }
+        // rclone appends to the log of the job, so the output of this run starts here
+        let log_offset = fs::metadata(settings.job_log_path(&job_id)).await.map_or(0, |m| m.len());
+        let mut child = match command.spawn()
+        {
+            Ok(child) => {
+                info!("✅ Rclone process started for job {}", job_id);
+                child
+            },
+            Err(e) => {
+                let error_msg = format!("{}: {}", SPAWN_FAILED_STATUS, e);
+                error!("❌ {}", error_msg);
+
+                record_attempt(&pool, &job_id, attempt, &error_msg, None, attempt_start).await;
+                let mut jobs = sync_jobs.lock().await;
+                if let Some(progress) = jobs.get_mut(&job_id) {
+                    progress.status = error_msg;
+                    progress.end_time = Some(Utc::now().timestamp());
+                    persist_job(&pool, job_record(&sync_request, progress, None, None)).await;
+                    metrics::record_job_finished(&sync_request, progress);
+                    notifications::job_finished(&pool, &settings, &sync_request, progress);
+                }
+                return;
+            }
+        };
+
+        // Record the process id so the job can be cancelled from any process. A cancellation
+        // requested while no process was running is kept and stops the new process right away.
+        {
             let mut jobs = sync_jobs.lock().await;
             if let Some(progress) = jobs.get_mut(&job_id) {
-                progress.status = error_msg;
+                let record = job_record(&sync_request, progress, child.id(), rc.as_ref());
+                if !persist_running_job(&pool, progress, record).await {
+                    info!("🛑 Job {} was cancelled before attempt {} started", job_id, attempt);
+                    let _ = child.start_kill();
+                }
             }
-            return;
+        }
+
+        // Wait for rclone to exit - no output processing needed as rclone writes to log file
+        let status = child.wait().await;
+
+        // The port is only reserved until rclone starts, another process may take it in between.
+        // rclone then exits before transferring anything and is started again on another port.
+        if let Some(rc) = &rc {
+            if status.as_ref().is_ok_and(|es| !es.success())
+                && rc_start_failed(&settings, &job_id, log_offset).await
+                && !is_cancelling(&pool, &job_id).await
+            {
+                rc_failures += 1;
+                warn!("⚠️ Remote control of job {} could not listen on port {}, starting rclone again", job_id, rc.port);
+                continue;
+            }
+        }
+
+        // Only a failure of the whole rclone process is retried, rclone retries single files itself
+        let retry = match (&status, &sync_request.retry) {
+            (Ok(es), Some(retry)) if !es.success() => {
+                es.code().and_then(|code| Some((code, retry.delay_before(attempt + 1, code)?)))
+            }
+            _ => None,
+        };
+        let Some((exit_code, delay)) = retry else {
+            break status;
+        };
+        if is_cancelling(&pool, &job_id).await {
+            break status;
+        }
+
+        let max_attempts = sync_request.retry.as_ref().map_or(1, |retry| retry.max_attempts);
+        warn!("🔁 Job {} failed with exit code {}, attempt {} of {} starts in {}s", job_id, exit_code, attempt + 1, max_attempts, delay);
+        record_attempt(&pool, &job_id, attempt, "Failed", Some(exit_code), attempt_start).await;
+        append_log_line(&settings, &job_id, &format!("Attempt {} failed with exit code {}, retrying in {}s", attempt, exit_code, delay)).await;
+        let retrying = {
+            let mut jobs = sync_jobs.lock().await;
+            match jobs.get_mut(&job_id) {
+                Some(progress) => {
+                    progress.status = RETRYING_STATUS.to_string();
+                    progress.exit_code = Some(exit_code);
+                    let record = job_record(&sync_request, progress, None, None);
+                    persist_running_job(&pool, progress, record).await
+                }
+                None => true,
+            }
+        };
+
+        if !retrying || !wait_before_retry(&pool, &job_id, delay).await {
+            break status;
+        }
+
+        attempt += 1;
+        attempt_start = Utc::now().timestamp();
+        append_log_line(&settings, &job_id, &format!("Starting attempt {} of {}", attempt, max_attempts)).await;
+        let mut jobs = sync_jobs.lock().await;
+        if let Some(progress) = jobs.get_mut(&job_id) {
+            progress.status = "Running".to_string();
+            progress.exit_code = None;
+            progress.attempt = attempt;
         }
     };
 
-    // Wait for rclone to exit - no output processing needed as rclone writes to log file
-    let status = child.wait().await;
+    let cancelled = is_cancelling(&pool, &job_id).await;
+    // Progress is otherwise only read from the log while someone polls the job
+    let final_stats = parse_latest_progress_from_log(&settings, &job_id).await;
+    save_failed_objects(&pool, &settings, &job_id).await;
+    let final_files = match joblogs::last_stats(&settings, &job_id).await {
+        Ok(stats) => stats.and_then(|s| s["transfers"].as_u64()).unwrap_or(0),
+        Err(_) => 0,
+    };
 
     // Update in-memory status based on exit code
     let mut jobs = sync_jobs.lock().await;
@@ -311,6 +1088,10 @@ async fn execute_sync(job_id: String, sync_request: SyncRequest, sync_jobs: Sync
         progress.end_time = Some(end_time);
         
         match &status {
+            Ok(_) if cancelled => {
+                progress.status = "Cancelled".to_string();
+                info!("🛑 Job {} cancelled", job_id);
+            }
             Ok(es) if es.success() => {
                 progress.status = "Completed".to_string();
                 progress.progress = 100.0;
@@ -325,13 +1106,84 @@ async fn execute_sync(job_id: String, sync_request: SyncRequest, sync_jobs: Sync
                 error!("💥 Job {} error: {}", job_id, e);
             }
         }
+
+        progress.exit_code = status.as_ref().ok().and_then(|es| es.code());
+        if let Some((_, transferred, total)) = final_stats {
+            progress.transferred = transferred;
+            progress.total = total;
+        }
+        progress.files = final_files;
+        record_attempt(&pool, &job_id, attempt, &progress.status, progress.exit_code, attempt_start).await;
+        persist_job(&pool, job_record(&sync_request, progress, None, None)).await;
+        metrics::record_job_finished(&sync_request, progress);
+        notifications::job_finished(&pool, &settings, &sync_request, progress);
+    }
+}
+
+/// Record one run of rclone in the attempts of a job
+async fn record_attempt(pool: &Pool<Sqlite>, job_id: &str, attempt: u32, status: &str, exit_code: Option<i32>, start_time: i64) {
+    let attempt = JobAttempt {
+        attempt,
+        status: status.to_string(),
+        exit_code: exit_code.map(i64::from),
+        start_time,
+        end_time: Some(Utc::now().timestamp()),
+    };
+    if let Err(e) = database::save_job_attempt(pool, job_id, &attempt).await {
+        error!("Failed to save attempt {} of job {}: {}", attempt.attempt, job_id, e);
+    }
+}
+
+/// Whether rclone exited because its remote control server could not start,
+/// judged from the part of the job log written since `log_offset`
+async fn rc_start_failed(settings: &Settings, job_id: &str, log_offset: u64) -> bool {
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    let Ok(mut file) = fs::File::open(settings.job_log_path(job_id)).await else {
+        return false;
+    };
+    let mut output = Vec::new();
+    if file.seek(std::io::SeekFrom::Start(log_offset)).await.is_err() || file.read_to_end(&mut output).await.is_err() {
+        return false;
+    }
+    String::from_utf8_lossy(&output).contains(RC_START_FAILED)
+}
+
+/// Wait for the next attempt of a job, false if the job was cancelled meanwhile
+async fn wait_before_retry(pool: &Pool<Sqlite>, job_id: &str, delay_secs: u64) -> bool {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(delay_secs);
+    loop {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if remaining.is_zero() {
+            return true;
+        }
+        // Cancellation may come from another process, so it is read from the database
+        tokio::time::sleep(remaining.min(CANCEL_POLL_INTERVAL)).await;
+        if is_cancelling(pool, job_id).await {
+            info!("🛑 Job {} cancelled while waiting for its next attempt", job_id);
+            return false;
+        }
+    }
+}
+
+/// Record the files a finished job failed to transfer
+async fn save_failed_objects(pool: &Pool<Sqlite>, settings: &Settings, job_id: &str) {
+    match joblogs::failed_objects(settings, job_id).await {
+        Ok(errors) if errors.is_empty() => {}
+        Ok(errors) => {
+            warn!("❌ Job {} failed to transfer {} files", job_id, errors.len());
+            if let Err(e) = database::save_job_errors(pool, job_id, &errors).await {
+                error!("Failed to save errors of job {}: {}", job_id, e);
+            }
+        }
+        Err(e) => warn!("Could not read errors of job {}: {}", job_id, e),
     }
 }
 
 /// Parse the latest progress from the rclone JSON log file
 /// Reads the last 10 lines and looks for the most recent stats entry
-async fn parse_latest_progress_from_log(job_id: &str) -> Option<(f64, u64, u64)> {
-    let log_file_path = format!("data/log/{}.log", job_id);
+async fn parse_latest_progress_from_log(settings: &Settings, job_id: &str) -> Option<(f64, u64, u64)> {
+    let log_file_path = settings.job_log_path(job_id);
     
     // Read the log file
     let content = match fs::read_to_string(&log_file_path).await {
@@ -412,7 +1264,7 @@ fn parse_json_stats(json: &serde_json::Value) -> Option<(f64, u64, u64)> {
         ) {
             let transfers_completed = stats.get("transfers").and_then(|v| v.as_u64()).unwrap_or(0);
             let transferring_list = stats.get("transferring").and_then(|v| v.as_array());
-            let is_transferring = transferring_list.map_or(false, |arr| !arr.is_empty());
+            let is_transferring = transferring_list.is_some_and(|arr| !arr.is_empty());
             
             let percent = if total_size > 0 {
                 if transferred == total_size && transfers_completed >= 1 && !is_transferring {
@@ -443,7 +1295,7 @@ fn parse_json_stats(json: &serde_json::Value) -> Option<(f64, u64, u64)> {
         // Check transfer completion status
         let transfers_completed = json.get("transfers").and_then(|v| v.as_u64()).unwrap_or(0);
         let transferring_list = json.get("transferring").and_then(|v| v.as_array());
-        let is_transferring = transferring_list.map_or(false, |arr| !arr.is_empty());
+        let is_transferring = transferring_list.is_some_and(|arr| !arr.is_empty());
         
         // Calculate accurate percentage
         let percent = if total_size > 0 {
@@ -494,7 +1346,7 @@ fn parse_traditional_progress(line: &str) -> Option<(f64, u64, u64)> {
     if line.contains("Transferred:") && line.contains('%') {
         if let Some(percent_pos) = line.find('%') {
             let before_percent = &line[..percent_pos];
-            if let Some(last_comma_or_space) = before_percent.rfind(|c: char| c == ',' || c == ' ') {
+            if let Some(last_comma_or_space) = before_percent.rfind([',', ' ']) {
                 let percent_str = before_percent[last_comma_or_space + 1..].trim();
                 if let Ok(progress) = percent_str.parse::<f64>() {
                     let (transferred, total) = parse_transferred_bytes(line);
@@ -513,7 +1365,7 @@ fn parse_transferred_bytes(line: &str) -> (u64, u64) {
         if let Some(slash) = after.find(" / ") {
             let transferred_part = after[..slash].trim();
             let rest = &after[slash + 3..];
-            let total_part = rest.split(|c| c == ',' || c == '%').next().unwrap_or(rest).trim();
+            let total_part = rest.split([',', '%']).next().unwrap_or(rest).trim();
             return (parse_byte_value(transferred_part), parse_byte_value(total_part));
         }
     }
@@ -3,9 +3,11 @@ use sqlx::{Pool, Sqlite};
 use uuid::Uuid;
 use chrono::Utc;
 use tracing::{info, warn, error};
-use crate::models::{ApiResponse, Task, TaskRequest, StartTaskRequest, SyncRequest};
+use crate::models::{ApiResponse, RetryPolicy, Task, TaskRequest, StartTaskRequest, SyncRequest};
 use crate::database;
 use crate::handlers::sync;
+use crate::settings::{self, Settings};
+use std::sync::Arc;
 
 fn validate_task_name(name: &str) -> Result<(), String> {
     if name.is_empty() {
@@ -24,6 +26,37 @@ fn validate_task_name(name: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Normalize the bandwidth limit of a task, an empty limit means none
+pub(crate) fn task_bwlimit(bwlimit: Option<String>) -> Result<Option<String>, String> {
+    match bwlimit {
+        Some(bwlimit) if !bwlimit.trim().is_empty() => settings::parse_bwlimit(&bwlimit).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Longest delay between two attempts of a job
+const MAX_RETRY_BACKOFF_SECS: u64 = 24 * 60 * 60;
+
+/// Most attempts of a job, so a failing job eventually ends
+const MAX_RETRY_ATTEMPTS: u32 = 10;
+
+/// Check the retry policy of a task, a single attempt means no retries
+pub(crate) fn task_retry(retry: Option<RetryPolicy>) -> Result<Option<RetryPolicy>, String> {
+    let Some(retry) = retry else {
+        return Ok(None);
+    };
+    if retry.max_attempts == 0 || retry.max_attempts > MAX_RETRY_ATTEMPTS {
+        return Err(format!("max_attempts must be between 1 and {}", MAX_RETRY_ATTEMPTS));
+    }
+    if retry.backoff_secs.iter().any(|&secs| secs > MAX_RETRY_BACKOFF_SECS) {
+        return Err(format!("Retry delays must not exceed {} seconds", MAX_RETRY_BACKOFF_SECS));
+    }
+    if retry.exit_codes.is_empty() || retry.exit_codes.iter().any(|&code| code <= 0) {
+        return Err("exit_codes must list rclone exit codes greater than 0".to_string());
+    }
+    Ok((retry.max_attempts > 1).then_some(retry))
+}
+
 pub async fn create_task(
     Extension(pool): Extension<Pool<Sqlite>>,
     Json(task_request): Json<TaskRequest>,
@@ -34,6 +67,14 @@ pub async fn create_task(
     if let Err(e) = validate_task_name(&task_request.name) {
         return ResponseJson(ApiResponse::error(&e));
     }
+    let bwlimit = match task_bwlimit(task_request.bwlimit) {
+        Ok(bwlimit) => bwlimit,
+        Err(e) => return ResponseJson(ApiResponse::error(&e)),
+    };
+    let retry = match task_retry(task_request.retry) {
+        Ok(retry) => retry,
+        Err(e) => return ResponseJson(ApiResponse::error(&e)),
+    };
     
     // Check if task name already exists
     match database::task_name_exists(&pool, &task_request.name).await {
@@ -55,6 +96,9 @@ pub async fn create_task(
         remote_path: task_request.remote_path,
         chunk_size: task_request.chunk_size,
         use_chunking: task_request.use_chunking.unwrap_or(false),
+        bwlimit,
+        resume_interrupted: task_request.resume_interrupted.unwrap_or(false),
+        retry,
         created_at: Utc::now(),
     };
     
@@ -107,8 +151,73 @@ pub async fn delete_task(
     }
 }
 
+pub async fn update_task(
+    Extension(pool): Extension<Pool<Sqlite>>,
+    Path(task_id): Path<String>,
+    Json(task_request): Json<TaskRequest>,
+) -> ResponseJson<ApiResponse<String>> {
+    info!("✏️ Updating task: {}", task_id);
+    
+    // Validate task name
+    if let Err(e) = validate_task_name(&task_request.name) {
+        return ResponseJson(ApiResponse::error(&e));
+    }
+    let bwlimit = match task_bwlimit(task_request.bwlimit) {
+        Ok(bwlimit) => bwlimit,
+        Err(e) => return ResponseJson(ApiResponse::error(&e)),
+    };
+    let retry = match task_retry(task_request.retry) {
+        Ok(retry) => retry,
+        Err(e) => return ResponseJson(ApiResponse::error(&e)),
+    };
+    
+    let existing = match database::get_all_tasks(&pool).await {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            error!("Failed to retrieve tasks: {}", e);
+            return ResponseJson(ApiResponse::error("Database error"));
+        }
+    };
+    
+    let Some(current) = existing.iter().find(|t| t.id == task_id) else {
+        warn!("Task {} not found for update", task_id);
+        return ResponseJson(ApiResponse::error("Task not found"));
+    };
+    
+    // Renaming must not collide with another task
+    if existing.iter().any(|t| t.id != task_id && t.name == task_request.name) {
+        return ResponseJson(ApiResponse::error("Task name already exists"));
+    }
+    
+    let task = Task {
+        id: task_id.clone(),
+        name: task_request.name,
+        source_path: task_request.source_path,
+        remote_name: task_request.remote_name,
+        remote_path: task_request.remote_path,
+        chunk_size: task_request.chunk_size,
+        use_chunking: task_request.use_chunking.unwrap_or(false),
+        bwlimit,
+        resume_interrupted: task_request.resume_interrupted.unwrap_or(false),
+        retry,
+        created_at: current.created_at,
+    };
+    
+    match database::update_task(&pool, &task).await {
+        Ok(_) => {
+            info!("✅ Task '{}' updated successfully", task.name);
+            ResponseJson(ApiResponse::success(task.id))
+        }
+        Err(e) => {
+            error!("Failed to update task {}: {}", task_id, e);
+            ResponseJson(ApiResponse::error("Failed to update task"))
+        }
+    }
+}
+
 pub async fn start_task(
     Extension(pool): Extension<Pool<Sqlite>>,
+    Extension(settings): Extension<Arc<Settings>>,
     Json(start_request): Json<StartTaskRequest>,
 ) -> ResponseJson<ApiResponse<String>> {
     info!("🚀 Starting task: {}", start_request.task_name);
@@ -133,9 +242,46 @@ pub async fn start_task(
         remote_path: task.remote_path,
         chunk_size: task.chunk_size,
         use_chunking: Some(task.use_chunking),
+        task_name: Some(task.name.clone()),
+        files_from: None,
+        bwlimit: task.bwlimit,
+        retry: task.retry,
     };
     
     // Start the sync job using existing sync handler
     info!("🔄 Converting task '{}' to sync job", task.name);
-    sync::start_sync(Json(sync_request)).await
-}
\ No newline at end of file
+    sync::start_sync(Extension(pool), Extension(settings), Json(sync_request)).await
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_attempt_means_no_retries() {
+        assert_eq!(task_retry(None), Ok(None));
+        assert_eq!(task_retry(Some(RetryPolicy::new(1))), Ok(None));
+        assert_eq!(task_retry(Some(RetryPolicy::new(3))), Ok(Some(RetryPolicy::new(3))));
+    }
+
+    #[test]
+    fn attempts_are_bounded() {
+        let error = Err(format!("max_attempts must be between 1 and {}", MAX_RETRY_ATTEMPTS));
+        assert_eq!(task_retry(Some(RetryPolicy::new(0))), error);
+        assert_eq!(task_retry(Some(RetryPolicy::new(u32::MAX))), error);
+        assert!(task_retry(Some(RetryPolicy::new(MAX_RETRY_ATTEMPTS))).is_ok());
+        assert_eq!(task_retry(Some(RetryPolicy::new(MAX_RETRY_ATTEMPTS + 1))), error);
+    }
+
+    #[test]
+    fn delays_and_exit_codes_are_checked() {
+        let policy = |backoff_secs: Vec<u64>, exit_codes: Vec<i32>| {
+            task_retry(Some(RetryPolicy { max_attempts: 3, backoff_secs, exit_codes }))
+        };
+
+        assert!(policy(vec![MAX_RETRY_BACKOFF_SECS], vec![1]).is_ok());
+        assert!(policy(vec![], vec![1]).is_ok());
+        assert!(policy(vec![60, MAX_RETRY_BACKOFF_SECS + 1], vec![1]).is_err());
+        assert!(policy(vec![60], vec![]).is_err());
+        assert!(policy(vec![60], vec![1, 0]).is_err());
+    }
+}
@@ -0,0 +1,384 @@
+use std::fmt;
+use std::path::Path;
+
+/// Format-preserving INI document used for rclone.conf.
+///
+/// Every line is kept verbatim, so comments, blank lines, key order and key case
+/// survive a load → modify → write cycle. Edits only touch the lines of the
+/// section being changed; everything else is written back byte-for-byte.
+#[derive(Debug, Clone, Default)]
+pub struct IniDocument {
+    /// Lines before the first section header
+    preamble: Vec<String>,
+    sections: Vec<IniSection>,
+}
+
+#[derive(Debug, Clone)]
+struct IniSection {
+    name: String,
+    /// Raw lines including the comments directly above the header, the header line and line endings
+    lines: Vec<String>,
+}
+
+/// Parsed view of a single raw line
+enum Line<'a> {
+    Header(&'a str),
+    Entry(&'a str, &'a str),
+    Other,
+}
+
+fn parse_line(line: &str) -> Line<'_> {
+    let trimmed = line.trim();
+
+    if trimmed.starts_with('[') && trimmed.ends_with(']') && trimmed.len() >= 2 {
+        return Line::Header(trimmed[1..trimmed.len() - 1].trim());
+    }
+
+    if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+        return Line::Other;
+    }
+
+    match trimmed.split_once('=') {
+        Some((key, value)) => Line::Entry(key.trim(), value.trim()),
+        None => Line::Other,
+    }
+}
+
+fn format_entry(key: &str, value: &str, line_ending: &str) -> String {
+    format!("{} = {}{}", key, value, line_ending)
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+fn is_comment(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with('#') || trimmed.starts_with(';')
+}
+
+impl IniDocument {
+    pub fn parse(content: &str) -> Self {
+        let mut document = IniDocument::default();
+
+        for line in content.split_inclusive('\n') {
+            match parse_line(line) {
+                Line::Header(name) => {
+                    // Comments directly above a header describe that section and belong to it
+                    let previous = match document.sections.last_mut() {
+                        Some(section) => &mut section.lines,
+                        None => &mut document.preamble,
+                    };
+                    let comments_start = previous.iter().rposition(|l| !is_comment(l)).map_or(0, |i| i + 1);
+                    let mut lines = previous.split_off(comments_start);
+                    lines.push(line.to_string());
+                    document.sections.push(IniSection { name: name.to_string(), lines });
+                }
+                _ => match document.sections.last_mut() {
+                    Some(section) => section.lines.push(line.to_string()),
+                    None => document.preamble.push(line.to_string()),
+                },
+            }
+        }
+
+        document
+    }
+
+    /// Load a document from disk, returning an empty document if the file does not exist
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(IniDocument::default());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+        Ok(Self::parse(&content))
+    }
+
+    pub fn section_names(&self) -> Vec<String> {
+        self.sections.iter().map(|s| s.name.clone()).collect()
+    }
+
+    /// Key/value pairs of a section in file order
+    pub fn entries(&self, section: &str) -> Option<Vec<(String, String)>> {
+        self.find(section).map(|s| {
+            s.lines
+                .iter()
+                .filter_map(|line| match parse_line(line) {
+                    Line::Entry(key, value) => Some((key.to_string(), value.to_string())),
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<String> {
+        self.entries(section)?
+            .into_iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Set a single key, creating the section if needed. Other keys are left untouched.
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        let line_ending = self.line_ending();
+        let section = self.find_or_create(section);
+
+        for line in section.lines.iter_mut() {
+            if let Line::Entry(existing_key, existing_value) = parse_line(line) {
+                if existing_key == key {
+                    if existing_value != value {
+                        let ending = if line.ends_with("\r\n") { "\r\n" } else { "\n" };
+                        *line = format_entry(key, value, ending);
+                    }
+                    return;
+                }
+            }
+        }
+
+        section.insert_entry(format_entry(key, value, &line_ending));
+    }
+
+    /// Make a section contain exactly the given entries.
+    /// Existing keys are updated in place, missing keys are removed and new keys are appended,
+    /// so an unchanged section is written back unchanged.
+    pub fn set_section(&mut self, section: &str, entries: &[(String, String)]) {
+        for (key, value) in entries {
+            self.set(section, key, value);
+        }
+
+        if let Some(section) = self.find_mut(section) {
+            section.lines.retain(|line| match parse_line(line) {
+                Line::Entry(key, _) => entries.iter().any(|(k, _)| k == key),
+                _ => true,
+            });
+        }
+    }
+
+    /// Remove a section with its entries and the comments directly above its header.
+    /// The blank lines that separated it from the previous section are dropped with the last section.
+    pub fn remove_section(&mut self, section: &str) -> bool {
+        let Some(position) = self.sections.iter().position(|s| s.name == section) else {
+            return false;
+        };
+
+        self.sections.remove(position);
+        if position == self.sections.len() {
+            let previous = match self.sections.last_mut() {
+                Some(last) => &mut last.lines,
+                None => &mut self.preamble,
+            };
+            while previous.last().is_some_and(|line| is_blank(line)) {
+                previous.pop();
+            }
+        }
+
+        true
+    }
+
+    fn find(&self, section: &str) -> Option<&IniSection> {
+        self.sections.iter().find(|s| s.name == section)
+    }
+
+    fn find_mut(&mut self, section: &str) -> Option<&mut IniSection> {
+        self.sections.iter_mut().find(|s| s.name == section)
+    }
+
+    fn find_or_create(&mut self, section: &str) -> &mut IniSection {
+        if self.find(section).is_none() {
+            let line_ending = self.line_ending();
+
+            // Terminate the previous last line and separate sections by a blank line
+            let previous = match self.sections.last_mut() {
+                Some(last) => Some(&mut last.lines),
+                None if !self.preamble.is_empty() => Some(&mut self.preamble),
+                None => None,
+            };
+            if let Some(lines) = previous {
+                if let Some(last_line) = lines.last_mut() {
+                    if !last_line.ends_with('\n') {
+                        last_line.push_str(&line_ending);
+                    }
+                }
+                if !lines.last().is_some_and(|l| is_blank(l)) {
+                    lines.push(line_ending.clone());
+                }
+            }
+
+            self.sections.push(IniSection {
+                name: section.to_string(),
+                lines: vec![format!("[{}]{}", section, line_ending)],
+            });
+        }
+
+        self.find_mut(section).expect("section exists")
+    }
+
+    /// Line ending used by the document, so edits do not mix styles
+    fn line_ending(&self) -> String {
+        let uses_crlf = self
+            .preamble
+            .iter()
+            .chain(self.sections.iter().flat_map(|s| s.lines.iter()))
+            .any(|line| line.ends_with("\r\n"));
+        if uses_crlf { "\r\n".to_string() } else { "\n".to_string() }
+    }
+}
+
+impl IniSection {
+    /// Insert a new entry after the last entry (or the header), before trailing blank lines and comments
+    fn insert_entry(&mut self, line: String) {
+        let last_entry = self
+            .lines
+            .iter()
+            .rposition(|l| matches!(parse_line(l), Line::Entry(..)))
+            .or_else(|| self.lines.iter().position(|l| matches!(parse_line(l), Line::Header(_))))
+            .unwrap_or(0);
+
+        let previous = &mut self.lines[last_entry];
+        if !previous.ends_with('\n') {
+            previous.push_str(if line.ends_with("\r\n") { "\r\n" } else { "\n" });
+        }
+
+        self.lines.insert(last_entry + 1, line);
+    }
+}
+
+impl fmt::Display for IniDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.preamble {
+            f.write_str(line)?;
+        }
+        for section in &self.sections {
+            for line in &section.lines {
+                f.write_str(line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "# rclone config\n\n[Drive]\ntype = drive\nClient_Id=abc\n; token below\ntoken = {\"a\":1}\n\n# backup remote\n[backup]\ntype = s3\nProvider = AWS\n";
+
+    fn entries(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn round_trip_preserves_content() {
+        let document = IniDocument::parse(CONFIG);
+
+        assert_eq!(document.to_string(), CONFIG);
+        assert_eq!(document.section_names(), vec!["Drive", "backup"]);
+        assert_eq!(document.get("Drive", "Client_Id").as_deref(), Some("abc"));
+        assert_eq!(document.get("Drive", "client_id"), None);
+    }
+
+    #[test]
+    fn set_existing_key_only_touches_that_line() {
+        let mut document = IniDocument::parse(CONFIG);
+        document.set("backup", "Provider", "Minio");
+
+        assert_eq!(
+            document.to_string(),
+            CONFIG.replace("Provider = AWS", "Provider = Minio")
+        );
+    }
+
+    #[test]
+    fn set_unchanged_value_keeps_original_formatting() {
+        let mut document = IniDocument::parse(CONFIG);
+        document.set("Drive", "Client_Id", "abc");
+
+        assert_eq!(document.to_string(), CONFIG);
+    }
+
+    #[test]
+    fn set_new_key_appends_after_last_entry() {
+        let mut document = IniDocument::parse(CONFIG);
+        document.set("Drive", "scope", "drive");
+
+        assert_eq!(
+            document.to_string(),
+            CONFIG.replace("{\"a\":1}\n", "{\"a\":1}\nscope = drive\n")
+        );
+    }
+
+    #[test]
+    fn set_new_section_is_separated_by_blank_line() {
+        let mut document = IniDocument::parse("[a]\ntype = local");
+        document.set("b", "type", "local");
+
+        assert_eq!(document.to_string(), "[a]\ntype = local\n\n[b]\ntype = local\n");
+    }
+
+    #[test]
+    fn set_keeps_crlf_line_endings() {
+        let mut document = IniDocument::parse("[a]\r\ntype = local\r\n");
+        document.set("a", "root", "/tmp");
+
+        assert_eq!(document.to_string(), "[a]\r\ntype = local\r\nroot = /tmp\r\n");
+    }
+
+    #[test]
+    fn set_section_removes_missing_keys() {
+        let mut document = IniDocument::parse(CONFIG);
+        document.set_section("Drive", &entries(&[("type", "drive"), ("Client_Id", "xyz")]));
+
+        assert_eq!(
+            document.to_string(),
+            "# rclone config\n\n[Drive]\ntype = drive\nClient_Id = xyz\n; token below\n\n# backup remote\n[backup]\ntype = s3\nProvider = AWS\n"
+        );
+        assert_eq!(
+            document.entries("Drive"),
+            Some(entries(&[("type", "drive"), ("Client_Id", "xyz")]))
+        );
+    }
+
+    #[test]
+    fn remove_section_keeps_comment_for_next_section() {
+        let mut document = IniDocument::parse(CONFIG);
+
+        assert!(document.remove_section("Drive"));
+        assert!(!document.remove_section("Drive"));
+        assert_eq!(
+            document.to_string(),
+            "# rclone config\n\n# backup remote\n[backup]\ntype = s3\nProvider = AWS\n"
+        );
+    }
+
+    #[test]
+    fn remove_last_section_removes_its_comments() {
+        let mut document = IniDocument::parse(CONFIG);
+
+        assert!(document.remove_section("backup"));
+        assert_eq!(document.section_names(), vec!["Drive"]);
+        assert_eq!(
+            document.to_string(),
+            "# rclone config\n\n[Drive]\ntype = drive\nClient_Id=abc\n; token below\ntoken = {\"a\":1}\n"
+        );
+    }
+
+    #[test]
+    fn remove_only_section_keeps_preamble() {
+        let mut document = IniDocument::parse("# rclone config\n\n# my drive\n[Drive]\ntype = drive\n");
+
+        assert!(document.remove_section("Drive"));
+        assert_eq!(document.to_string(), "# rclone config\n");
+    }
+
+    #[test]
+    fn set_in_commented_empty_section_inserts_after_header() {
+        let mut document = IniDocument::parse("[a]\ntype = local\n\n# empty remote\n[b]\n");
+        document.set("b", "type", "local");
+
+        assert_eq!(document.to_string(), "[a]\ntype = local\n\n# empty remote\n[b]\ntype = local\n");
+    }
+}
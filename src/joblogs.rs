@@ -0,0 +1,463 @@
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use sqlx::{Pool, Sqlite};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::fs;
+use tracing::{debug, error, info, warn};
+use crate::database;
+use crate::handlers::sync;
+use crate::models::{JobError, LogEntry, LogLevel, LogPage, LogQuery};
+use crate::settings::Settings;
+
+/// Time between two janitor runs
+const JANITOR_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Log files without a job record are only removed once they were untouched for this long,
+/// so logs of jobs being registered by another process are left alone
+const ORPHAN_GRACE: Duration = Duration::from_secs(3600);
+
+/// Entries returned by a log query without `limit`
+const DEFAULT_QUERY_LIMIT: usize = 1000;
+
+/// Read the log of a job, plain or compressed
+pub async fn read(settings: &Settings, job_id: &str) -> io::Result<String> {
+    match fs::read_to_string(settings.job_log_path(job_id)).await {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let compressed = fs::read(settings.job_log_gz_path(job_id)).await?;
+            tokio::task::spawn_blocking(move || {
+                let mut content = String::new();
+                GzDecoder::new(compressed.as_slice()).read_to_string(&mut content)?;
+                Ok(content)
+            })
+            .await?
+        }
+        result => result,
+    }
+}
+
+/// Open the log of a job for reading line by line, plain or compressed
+fn open(settings: &Settings, job_id: &str) -> io::Result<Box<dyn BufRead + Send>> {
+    match std::fs::File::open(settings.job_log_path(job_id)) {
+        Ok(file) => Ok(Box::new(BufReader::new(file))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let file = std::fs::File::open(settings.job_log_gz_path(job_id))?;
+            Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Parse a log line. rclone writes JSON objects; other lines (the header written
+/// when the job starts, rclone output before logging is set up) become `info` entries.
+fn parse_line(line: usize, text: &str) -> LogEntry {
+    if let Ok(serde_json::Value::Object(mut json)) = serde_json::from_str(text) {
+        let field = |json: &serde_json::Map<String, serde_json::Value>, key: &str| {
+            json.get(key).and_then(|v| v.as_str()).map(|v| v.to_string())
+        };
+        return LogEntry {
+            line,
+            time: field(&json, "time").and_then(|t| chrono::DateTime::parse_from_rfc3339(&t).ok()),
+            level: json
+                .get("level")
+                .and_then(|level| serde_json::from_value(level.clone()).ok())
+                .unwrap_or(LogLevel::Info),
+            object: field(&json, "object").filter(|o| !o.is_empty()),
+            msg: field(&json, "msg").unwrap_or_default().trim_end().to_string(),
+            stats: json.remove("stats"),
+        };
+    }
+
+    // `[2024-01-01 12:00:00 UTC] Job ... started`
+    let time = text
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .and_then(|(time, _)| chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S UTC").ok())
+        .map(|time| time.and_utc().fixed_offset());
+    LogEntry { line, time, level: LogLevel::Info, object: None, msg: text.to_string(), stats: None }
+}
+
+fn matches(entry: &LogEntry, query: &LogQuery, object: Option<&str>) -> bool {
+    if entry.stats.is_some() && !query.stats {
+        return false;
+    }
+    if query.level.is_some_and(|level| entry.level < level) {
+        return false;
+    }
+    if let Some(object) = object {
+        if !entry.object.as_deref().is_some_and(|o| o.to_lowercase().contains(object)) {
+            return false;
+        }
+    }
+    if query.since.is_some() || query.until.is_some() {
+        let Some(time) = entry.time else {
+            return false;
+        };
+        if query.since.is_some_and(|since| time < since) || query.until.is_some_and(|until| time > until) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Entries of a job log matching a query. The log is streamed, so only the
+/// returned page is held in memory.
+pub async fn query(settings: &Settings, job_id: &str, query: LogQuery) -> io::Result<LogPage> {
+    let mut reader = open(settings, job_id)?;
+    let job_id = job_id.to_string();
+
+    tokio::task::spawn_blocking(move || {
+        let limit = query.limit.unwrap_or(DEFAULT_QUERY_LIMIT);
+        // The tail is held in memory until the end of the log, it is bounded like a page
+        let tail = query.tail.map(|tail| tail.min(limit));
+        let object = query.object.as_ref().map(|o| o.to_lowercase());
+        let mut entries = VecDeque::new();
+        let mut total = 0;
+        let mut buffer = Vec::new();
+        let mut line = 0;
+
+        while reader.read_until(b'\n', &mut buffer)? > 0 {
+            line += 1;
+            let text = String::from_utf8_lossy(&buffer);
+            let text = text.trim_end();
+            if !text.is_empty() {
+                let entry = parse_line(line, text);
+                if matches(&entry, &query, object.as_deref()) {
+                    total += 1;
+                    match tail {
+                        Some(tail) => {
+                            entries.push_back(entry);
+                            if entries.len() > tail {
+                                entries.pop_front();
+                            }
+                        }
+                        None if total > query.offset && entries.len() < limit => entries.push_back(entry),
+                        None => {}
+                    }
+                }
+            }
+            buffer.clear();
+        }
+
+        let offset = if tail.is_some() { total - entries.len() } else { query.offset };
+        Ok(LogPage { job_id, total, offset, entries: entries.into() })
+    })
+    .await?
+}
+
+/// Last stats block rclone wrote to the log of a job
+pub async fn last_stats(settings: &Settings, job_id: &str) -> io::Result<Option<serde_json::Value>> {
+    let mut reader = open(settings, job_id)?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut stats = None;
+        let mut buffer = Vec::new();
+        while reader.read_until(b'\n', &mut buffer)? > 0 {
+            if let Ok(serde_json::Value::Object(mut json)) = serde_json::from_slice(&buffer) {
+                if let Some(block) = json.remove("stats") {
+                    stats = Some(block);
+                }
+            }
+            buffer.clear();
+        }
+        Ok(stats)
+    })
+    .await?
+}
+
+/// Files a job failed to transfer, from the error entries of its log. rclone repeats
+/// a failed file in each of its `--retries` attempts; a file copied by a later attempt
+/// is not reported.
+pub async fn failed_objects(settings: &Settings, job_id: &str) -> io::Result<Vec<JobError>> {
+    let reader = open(settings, job_id)?;
+    tokio::task::spawn_blocking(move || collect_failed_objects(reader)).await?
+}
+
+fn collect_failed_objects(mut reader: impl BufRead) -> io::Result<Vec<JobError>> {
+    let mut failed: BTreeMap<String, JobError> = BTreeMap::new();
+    let mut buffer = Vec::new();
+    let mut line = 0;
+
+    while reader.read_until(b'\n', &mut buffer)? > 0 {
+        line += 1;
+        let entry = parse_line(line, String::from_utf8_lossy(&buffer).trim_end());
+        buffer.clear();
+
+        // Errors without an object are attempt summaries such as "Attempt 1/3 failed with 2 errors"
+        let Some(object) = entry.object else {
+            continue;
+        };
+        if entry.level >= LogLevel::Error {
+            failed
+                .entry(object.clone())
+                .and_modify(|e| {
+                    e.message = entry.msg.clone();
+                    e.retries += 1;
+                })
+                .or_insert(JobError { object, message: entry.msg, retries: 0 });
+        } else if entry.msg.contains("Copied (") {
+            // "Copied (new)", "Copied (replaced existing)", "Multi-thread Copied (new)", ...
+            failed.remove(&object);
+        }
+    }
+    Ok(failed.into_values().collect())
+}
+
+/// Delete the log of a job, plain and compressed, and its file list
+pub async fn remove(settings: &Settings, job_id: &str) -> io::Result<()> {
+    let _ = fs::remove_file(settings.job_files_from_path(job_id)).await;
+
+    let mut found = false;
+    for path in [settings.job_log_path(job_id), settings.job_log_gz_path(job_id)] {
+        match fs::remove_file(&path).await {
+            Ok(()) => found = true,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    if found {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::NotFound, "no log file"))
+    }
+}
+
+/// Replace the plain log of a finished job by a gzip file
+async fn compress(settings: &Settings, job_id: &str) -> io::Result<()> {
+    let plain = settings.job_log_path(job_id);
+    let gz = settings.job_log_gz_path(job_id);
+    let content = fs::read(&plain).await?;
+
+    let partial = gz.with_extension("gz.partial");
+    let target = partial.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut encoder = GzEncoder::new(std::fs::File::create(&target)?, Compression::default());
+        encoder.write_all(&content)?;
+        encoder.finish()?.sync_all()
+    })
+    .await??;
+
+    fs::rename(&partial, &gz).await?;
+    fs::remove_file(&plain).await
+}
+
+/// Outcome of a janitor run
+#[derive(Debug, Default)]
+pub struct CleanupSummary {
+    pub expired_jobs: usize,
+    pub orphaned_logs: usize,
+    pub compressed_logs: usize,
+}
+
+/// Apply the retention policy: delete finished jobs (records and logs) beyond the
+/// configured number of runs per task or age, keeping their totals for the transfer
+/// history, delete logs without a job, and
+/// compress the remaining logs of finished jobs when enabled.
+pub async fn clean_up(pool: &Pool<Sqlite>, settings: &Settings) -> anyhow::Result<CleanupSummary> {
+    let mut summary = CleanupSummary::default();
+    let records = database::get_all_jobs(pool).await?;
+    let now = chrono::Utc::now().timestamp();
+    let max_age = settings.log_keep_days as i64 * 86400;
+
+    // Records are ordered newest first; jobs without a task count as one group
+    let mut runs_per_task: HashMap<Option<&str>, usize> = HashMap::new();
+    let mut kept = Vec::new();
+    for record in records.iter().filter(|r| sync::is_finished(&r.status)) {
+        let run = runs_per_task.entry(record.task_name.as_deref()).or_default();
+        *run += 1;
+
+        let too_many = settings.log_keep_runs > 0 && *run > settings.log_keep_runs;
+        let too_old = max_age > 0 && now - record.end_time.unwrap_or(record.start_time) > max_age;
+        if !(too_many || too_old) {
+            kept.push(record.id.as_str());
+            continue;
+        }
+
+        debug!("🧹 Removing job {} ({})", record.id, if too_many { "run limit" } else { "age limit" });
+        sync::forget_job(&record.id).await;
+        database::roll_up_and_delete_job(pool, &record.id).await?;
+        if let Err(e) = remove(settings, &record.id).await {
+            debug!("⚠️ Could not delete log of job {}: {}", record.id, e);
+        }
+        summary.expired_jobs += 1;
+    }
+
+    let known: HashSet<&str> = records.iter().map(|r| r.id.as_str()).collect();
+    summary.orphaned_logs = remove_orphaned_logs(settings, &known).await?;
+
+    if settings.log_compress {
+        for job_id in kept {
+            if !fs::try_exists(settings.job_log_path(job_id)).await.unwrap_or(false) {
+                continue;
+            }
+            match compress(settings, job_id).await {
+                Ok(()) => summary.compressed_logs += 1,
+                Err(e) => warn!("Could not compress log of job {}: {}", job_id, e),
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Delete job logs that belong to no known job and were not written to recently
+async fn remove_orphaned_logs(settings: &Settings, known: &HashSet<&str>) -> io::Result<usize> {
+    let mut entries = match fs::read_dir(&settings.log_dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+
+    let mut removed = 0;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        // Only job logs and file lists, keep .gitkeep and other files
+        let Some(job_id) = [".log", ".log.gz", ".files"].iter().find_map(|suffix| name.strip_suffix(suffix)) else {
+            continue;
+        };
+        if known.contains(job_id) {
+            continue;
+        }
+
+        let modified = entry.metadata().await?.modified()?;
+        let idle = SystemTime::now().duration_since(modified).unwrap_or_default();
+        if idle < ORPHAN_GRACE {
+            continue;
+        }
+
+        match fs::remove_file(entry.path()).await {
+            Ok(()) => {
+                removed += 1;
+                debug!("🧹 Removed orphaned log file: {}", name);
+            }
+            Err(e) => warn!("Could not remove orphaned log file {}: {}", name, e),
+        }
+    }
+    Ok(removed)
+}
+
+/// Run the retention policy at startup and then periodically, for the lifetime of the server
+pub async fn run_janitor(pool: Pool<Sqlite>, settings: Arc<Settings>) {
+    let mut interval = tokio::time::interval(JANITOR_INTERVAL);
+    loop {
+        interval.tick().await;
+        match clean_up(&pool, &settings).await {
+            Ok(summary) if summary.expired_jobs + summary.orphaned_logs + summary.compressed_logs > 0 => info!(
+                expired_jobs = summary.expired_jobs,
+                orphaned_logs = summary.orphaned_logs,
+                compressed_logs = summary.compressed_logs,
+                "🧹 Log retention applied"
+            ),
+            Ok(_) => debug!("🧹 Log retention: nothing to clean up"),
+            Err(e) => error!("Log retention failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ERROR_LINE: &str = r#"{"level":"error","msg":"Failed to copy: permission denied\n","object":"docs/a.txt","objectType":"*local.Object","source":"operations/copy.go:230","time":"2025-03-01T10:00:02.5+01:00"}"#;
+    const STATS_LINE: &str = r#"{"level":"info","msg":"Transferred: 1 B / 1 B, 100%","source":"accounting/stats.go:516","stats":{"bytes":1,"transfers":1},"time":"2025-03-01T10:00:05+01:00"}"#;
+
+    fn time(text: &str) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&chrono::Utc)
+    }
+
+    #[test]
+    fn json_lines_are_parsed() {
+        let entry = parse_line(3, ERROR_LINE);
+        assert_eq!(entry.line, 3);
+        assert_eq!(entry.level, LogLevel::Error);
+        assert_eq!(entry.object.as_deref(), Some("docs/a.txt"));
+        assert_eq!(entry.msg, "Failed to copy: permission denied");
+        assert_eq!(entry.time.map(|t| t.with_timezone(&chrono::Utc)), Some(time("2025-03-01T09:00:02.5Z")));
+        assert!(entry.stats.is_none());
+
+        let stats = parse_line(4, STATS_LINE);
+        assert_eq!(stats.object, None);
+        assert_eq!(stats.stats, Some(serde_json::json!({"bytes": 1, "transfers": 1})));
+    }
+
+    #[test]
+    fn header_lines_are_info_entries() {
+        let entry = parse_line(1, "[2025-03-01 09:00:00 UTC] Job 1234 started: /data -> backup:data");
+        assert_eq!(entry.level, LogLevel::Info);
+        assert_eq!(entry.object, None);
+        assert_eq!(entry.msg, "[2025-03-01 09:00:00 UTC] Job 1234 started: /data -> backup:data");
+        assert_eq!(entry.time.map(|t| t.with_timezone(&chrono::Utc)), Some(time("2025-03-01T09:00:00Z")));
+
+        let plain = parse_line(2, "rclone: command not found");
+        assert_eq!(plain.time, None);
+        assert_eq!(plain.level, LogLevel::Info);
+    }
+
+    #[test]
+    fn unknown_level_is_info() {
+        let entry = parse_line(1, r#"{"level":"verbose","msg":"hello","object":""}"#);
+        assert_eq!(entry.level, LogLevel::Info);
+        assert_eq!(entry.object, None);
+
+        assert_eq!(parse_line(1, r#"{"level":"warn","msg":"slow"}"#).level, LogLevel::Warning);
+    }
+
+    #[test]
+    fn entries_match_level_and_object() {
+        let entry = parse_line(1, ERROR_LINE);
+        let query = |level| LogQuery { level, ..Default::default() };
+
+        assert!(matches(&entry, &query(Some(LogLevel::Error)), None));
+        assert!(!matches(&entry, &query(Some(LogLevel::Critical)), None));
+        assert!(matches(&entry, &query(None), Some("docs/")));
+        assert!(!matches(&entry, &query(None), Some("other")));
+        assert!(!matches(&parse_line(2, "[2025-03-01 09:00:00 UTC] Job started"), &query(None), Some("docs")));
+    }
+
+    #[test]
+    fn entries_match_time_range() {
+        let entry = parse_line(1, ERROR_LINE);
+        let range = |since: &str, until: &str| LogQuery {
+            since: Some(time(since)),
+            until: Some(time(until)),
+            ..Default::default()
+        };
+
+        assert!(matches(&entry, &range("2025-03-01T09:00:00Z", "2025-03-01T09:00:03Z"), None));
+        assert!(!matches(&entry, &range("2025-03-01T09:00:03Z", "2025-03-01T10:00:00Z"), None));
+        assert!(!matches(&entry, &range("2025-03-01T08:00:00Z", "2025-03-01T09:00:02Z"), None));
+        // Entries without a time cannot be placed in a range
+        assert!(!matches(&parse_line(2, "rclone: started"), &range("2000-01-01T00:00:00Z", "2100-01-01T00:00:00Z"), None));
+    }
+
+    #[test]
+    fn failed_objects_count_retries_and_skip_copied_files() {
+        let log = [
+            "[2025-03-01 09:00:00 UTC] Job 1234 started: /data -> backup:data",
+            ERROR_LINE,
+            r#"{"level":"error","msg":"Failed to copy: connection reset","object":"big.bin","objectType":"*local.Object","source":"operations/copy.go:230","time":"2025-03-01T10:00:03+01:00"}"#,
+            r#"{"level":"error","msg":"Attempt 1/3 failed with 2 errors and: permission denied","source":"fs/sync.go:1230","time":"2025-03-01T10:00:04+01:00"}"#,
+            r#"{"level":"error","msg":"Failed to copy: read only file system","object":"docs/a.txt","objectType":"*local.Object","source":"operations/copy.go:230","time":"2025-03-01T10:00:06+01:00"}"#,
+            r#"{"level":"info","msg":"Multi-thread Copied (new)","object":"big.bin","objectType":"*local.Object","size":104857600,"source":"operations/multithread.go:215","time":"2025-03-01T10:00:09+01:00"}"#,
+            r#"{"level":"error","msg":"Failed to copy: quota exceeded","object":"c.txt","objectType":"*local.Object","source":"operations/copy.go:230","time":"2025-03-01T10:00:10+01:00"}"#,
+            r#"{"level":"info","msg":"Copied (replaced existing)","object":"c.txt","objectType":"*local.Object","size":12,"source":"operations/copy.go:360","time":"2025-03-01T10:00:11+01:00"}"#,
+            r#"{"level":"error","msg":"Attempt 2/3 failed with 1 errors and: read only file system","source":"fs/sync.go:1230","time":"2025-03-01T10:00:12+01:00"}"#,
+        ]
+        .join("\n");
+
+        let failed = collect_failed_objects(log.as_bytes()).unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].object, "docs/a.txt");
+        assert_eq!(failed[0].message, "Failed to copy: read only file system");
+        assert_eq!(failed[0].retries, 1);
+    }
+
+    #[test]
+    fn stats_entries_are_hidden_by_default() {
+        let entry = parse_line(1, STATS_LINE);
+
+        assert!(!matches(&entry, &LogQuery::default(), None));
+        assert!(matches(&entry, &LogQuery { stats: true, ..Default::default() }, None));
+    }
+}
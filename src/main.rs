@@ -1,6 +1,6 @@
 use axum::{
-    extract::{Path, Request},
-    routing::{get, post, delete},
+    extract::{MatchedPath, Path, Query, Request},
+    routing::{get, post, put, delete},
     Router,
     Extension,
     response::Html,
@@ -14,17 +14,24 @@ use tower_http::{
     services::ServeDir,
     trace::TraceLayer,
 };
+use tracing::{debug, error, info, info_span, warn, Instrument};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
-use tracing;
 use clap::Parser;
 use std::env;
 use dotenvy::{dotenv, from_filename_override};
-use chrono;
 
 mod handlers;
 mod models;
 mod config_manager;
 mod database;
+mod bundle;
+mod ini;
+mod settings;
+mod cli;
+mod client;
+mod notifications;
+mod metrics;
+mod joblogs;
 
 #[derive(Parser)]
 #[command(name = "rclone-gui")]
@@ -34,113 +41,247 @@ struct Args {
     memory_mode: bool,
     #[arg(long, default_value = "127.0.0.1:8080", help = "Address to bind the server to")]
     bind: String,
-    #[arg(long, help = "Start a task by name and exit")]
+    #[arg(long, help = "Start a task by name and exit (same as `task run`)")]
     start_task: Option<String>,
+    #[arg(long, global = true, value_enum, default_value_t = cli::OutputFormat::Text, help = "Output format of CLI commands")]
+    output: cli::OutputFormat,
+    #[arg(long, global = true, help = "Shorthand for --output json")]
+    json: bool,
+    #[arg(long, global = true, env = "RCLONE_GUI_SERVER", help = "Run the task on a running server at this URL instead of in-process")]
+    server: Option<String>,
+    #[arg(long, global = true, value_enum, env = "RCLONE_GUI_LOG_FORMAT", default_value_t = LogFormat::Text, help = "Format of log output")]
+    log_format: LogFormat,
+    #[command(flatten)]
+    settings: settings::SettingsArgs,
+    #[command(subcommand)]
+    command: Option<cli::Command>,
+}
+
+/// Format of log output on stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum LogFormat {
+    /// Compact human readable lines
+    Text,
+    /// One JSON object per event, including the fields of its spans (request_id, job_id, task_name)
+    Json,
 }
 
 #[tokio::main]
 async fn main() {
-    // Load environment variables with detailed feedback
-    load_environment_config();
-    
-    // Clean up any leftover log files from previous runs
-    cleanup_orphaned_log_files().await;
-    
-    // Initialize enhanced tracing
-    setup_tracing();
+    // Load environment variables first, they provide defaults for the arguments
+    let (env_loaded, env_local_loaded) = load_environment_files();
+
     let args = Args::parse();
-    
-    println!("⚙️  Command line arguments:");
-    println!("   Memory mode: {}", args.memory_mode);
-    println!("   Bind address: {}", args.bind);
-    if let Some(ref task_name) = args.start_task {
-        println!("   Start task: {}", task_name);
+    let json = args.json || args.output == cli::OutputFormat::Json;
+    let settings = Arc::new(settings::Settings::from(args.settings.clone()));
+    // Client mode: the job runs on the server, so no local database or logs are touched
+    if let Some(server) = args.server {
+        setup_tracing(false, args.log_format);
+        let task_name = match (args.start_task, args.command) {
+            (Some(task_name), _) => task_name,
+            (None, Some(cli::Command::Task(cli::TaskCommand::Run { name }))) => name,
+            _ => {
+                eprintln!("❌ --server is only supported together with --start-task or `task run`");
+                std::process::exit(2);
+            }
+        };
+        return cli::run_task(cli::TaskRunner::Server(client::ApiClient::new(&server)), task_name, json).await;
+    }
+
+    let serving = args.start_task.is_none() && matches!(args.command, None | Some(cli::Command::Serve));
+
+    // Initialize enhanced tracing; CLI commands keep stdout for their results
+    setup_tracing(serving, args.log_format);
+
+    if serving {
+        report_environment_config(env_loaded, env_local_loaded);
+    }
+
+    if serving {
+        info!(
+            memory_mode = args.memory_mode,
+            bind = %args.bind,
+            data_dir = %settings.data_dir.display(),
+            rclone_config = %settings.rclone_config.display(),
+            rclone_binary = %settings.rclone_binary,
+            log_dir = %settings.log_dir.display(),
+            database = %settings.database_url,
+            max_upload_size = settings.max_upload_size,
+            log_keep_runs = settings.log_keep_runs,
+            log_keep_days = settings.log_keep_days,
+            log_compress = settings.log_compress,
+            "⚙️  Settings loaded"
+        );
     }
-    println!("");
 
     // Initialize database
-    let db_pool = match database::init_database().await {
+    let db_pool = match database::init_database(&settings).await {
         Ok(pool) => pool,
         Err(e) => {
-            eprintln!("❌ Failed to initialize database: {}", e);
+            error!("❌ Failed to initialize database: {}", e);
             std::process::exit(1);
         }
     };
     
     // Handle CLI task execution
     if let Some(task_name) = args.start_task {
-        return handle_cli_task_execution(db_pool, task_name).await;
+        return cli::run_task(cli::TaskRunner::Local { pool: db_pool, settings }, task_name, json).await;
+    }
+
+    // Handle CLI subcommands
+    if let Some(command) = args.command.filter(|c| !matches!(c, cli::Command::Serve)) {
+        return cli::run(command, db_pool, settings, json).await;
     }
     
-    let config_manager = Arc::new(config_manager::ConfigManager::new(args.memory_mode));
+    let config_manager = Arc::new(config_manager::ConfigManager::new(args.memory_mode, &settings));
     
     if args.memory_mode {
-        println!("💾 Running in memory mode:");
-        println!("   ⚠️  Configurations will not be saved to file automatically");
-        println!("   📥 Loading existing configs from file into memory...");
+        info!("💾 Running in memory mode, configurations are not saved to file automatically");
         
         // Load existing configs from file into memory
         if let Err(e) = config_manager.load_from_file_to_memory().await {
-            eprintln!("   ❌ Warning: Could not load existing configs from file: {}", e);
+            warn!("❌ Could not load existing configs from file: {}", e);
         } else {
-            println!("   ✅ Existing configs loaded successfully");
+            info!("✅ Existing configs loaded into memory");
         }
-        println!("");
+        config_manager.spawn_file_watcher();
+        info!("👀 Watching rclone.conf for external changes");
     } else {
-        println!("💾 Running in persistent mode:");
-        println!("   ✅ Configurations will be saved to file automatically");
-        println!("");
+        info!("💾 Running in persistent mode, configurations are saved to file automatically");
     }
 
+    // Jobs of a previous server process lost their rclone process with it
+    handlers::sync::recover_interrupted_jobs(&db_pool, &settings).await;
+
+    // Apply the log retention policy in the background (CLI commands
+    // may run next to a live server and leave its logs to it)
+    tokio::spawn(joblogs::run_janitor(db_pool.clone(), settings.clone()));
+
     // Log all registered routes
-    println!("📋 Registering API routes:");
-    println!("   GET    /                              -> serve_index");
-    println!("   GET    /api/configs                   -> get_configs");
-    println!("   POST   /api/configs                   -> save_config");
-    println!("   DELETE /api/configs/:name             -> delete_config");
-    println!("   GET    /api/configs/:name/edit        -> get_config_for_edit");
-    println!("   POST   /api/configs/persist           -> persist_configs");
-    println!("   GET    /api/files/local               -> list_local_files");
-    println!("   GET    /api/files/remote              -> list_remote_files");
-    println!("   POST   /api/sync                      -> start_sync");
-    println!("   GET    /api/sync                      -> list_sync_jobs");
-    println!("   GET    /api/sync-log/:job_id          -> get_sync_log (temp route)");
-    println!("   DELETE /api/sync-delete/:job_id       -> delete_sync_job (temp route)");
-    println!("   GET    /api/sync/:job_id/log          -> get_sync_log");
-    println!("   GET    /api/sync/:job_id              -> get_sync_progress");
-    println!("   DELETE /api/sync/:job_id              -> delete_sync_job");
-    println!("   GET    /api/tasks                     -> get_tasks");
-    println!("   POST   /api/tasks                     -> create_task");
-    println!("   DELETE /api/tasks/:task_id            -> delete_task");
-    println!("   POST   /api/tasks/start               -> start_task");
-    println!("   STATIC /static/*                      -> serve static files");
-    println!("");
+    info!("📋 Registering API routes");
+    debug!("GET    /                              -> serve_index");
+    debug!("GET    /metrics                       -> serve_metrics");
+    debug!("GET    /healthz                       -> healthz");
+    debug!("GET    /readyz                        -> readyz");
+    debug!("GET    /api/configs                   -> get_configs");
+    debug!("POST   /api/configs                   -> save_config");
+    debug!("DELETE /api/configs/:name             -> delete_config");
+    debug!("GET    /api/configs/:name/edit        -> get_config_for_edit");
+    debug!("POST   /api/configs/:name/test        -> test_config");
+    debug!("POST   /api/configs/persist           -> persist_configs");
+    debug!("GET    /api/configs/backups           -> list_config_backups");
+    debug!("POST   /api/configs/backups/:id/restore -> restore_config_backup");
+    debug!("GET    /api/configs/sync-status       -> get_config_sync_status");
+    debug!("POST   /api/configs/resolve           -> resolve_config_conflict");
+    debug!("GET    /api/files/local               -> list_local_files");
+    debug!("GET    /api/files/remote              -> list_remote_files");
+    debug!("POST   /api/files/remote/mkdir        -> make_remote_directory");
+    debug!("POST   /api/files/remote/delete       -> delete_remote_path");
+    debug!("POST   /api/files/remote/move         -> move_remote_path");
+    debug!("POST   /api/files/remote/copy         -> copy_remote_path");
+    debug!("GET    /api/files/remote/download     -> download_remote_file");
+    debug!("POST   /api/files/remote/upload       -> upload_remote_file");
+    debug!("POST   /api/files/size                -> start_size_calculation");
+    debug!("GET    /api/files/size/:id            -> get_size_calculation");
+    debug!("DELETE /api/files/size/:id            -> cancel_size_calculation");
+    debug!("GET    /api/files/compare             -> compare_directories");
+    debug!("POST   /api/sync                      -> start_sync");
+    debug!("GET    /api/sync                      -> list_sync_jobs");
+    debug!("GET    /api/sync-log/:job_id          -> get_sync_log (temp route)");
+    debug!("DELETE /api/sync-delete/:job_id       -> delete_sync_job (temp route)");
+    debug!("GET    /api/sync/:job_id/log          -> get_sync_log");
+    debug!("GET    /api/sync/:job_id/log/entries  -> get_sync_log_entries");
+    debug!("GET    /api/sync/:job_id/errors       -> get_sync_errors");
+    debug!("GET    /api/sync/:job_id/attempts     -> get_sync_attempts");
+    debug!("POST   /api/sync/:job_id/retry-failed -> retry_failed_files");
+    debug!("POST   /api/sync/:job_id/resume       -> resume_job");
+    debug!("GET    /api/sync/:job_id/bwlimit      -> get_job_bwlimit");
+    debug!("POST   /api/sync/:job_id/bwlimit      -> set_job_bwlimit");
+    debug!("GET    /api/sync/:job_id              -> get_sync_progress");
+    debug!("DELETE /api/sync/:job_id              -> delete_sync_job");
+    debug!("POST   /api/sync/:job_id/cancel       -> cancel_sync_job");
+    debug!("GET    /api/jobs                      -> list_job_records");
+    debug!("GET    /api/stats/transfers           -> get_transfer_history");
+    debug!("GET    /api/tasks                     -> get_tasks");
+    debug!("POST   /api/tasks                     -> create_task");
+    debug!("PUT    /api/tasks/:task_id            -> update_task");
+    debug!("DELETE /api/tasks/:task_id            -> delete_task");
+    debug!("POST   /api/tasks/start               -> start_task");
+    debug!("GET    /api/tasks/:task_id/notifications -> get_task_notifications");
+    debug!("PUT    /api/tasks/:task_id/notifications -> set_task_notifications");
+    debug!("GET    /api/notifications/channels    -> list_channels");
+    debug!("POST   /api/notifications/channels    -> create_channel");
+    debug!("PUT    /api/notifications/channels/:id -> update_channel");
+    debug!("DELETE /api/notifications/channels/:id -> delete_channel");
+    debug!("POST   /api/notifications/channels/:id/test -> test_channel");
+    debug!("POST   /api/bundle/export             -> export_bundle");
+    debug!("POST   /api/bundle/import             -> import_bundle");
+    debug!("STATIC /static/*                      -> serve static files");
 
     let app = Router::new()
         .route("/", get(serve_index))
+        .route("/metrics", get(serve_metrics))
+        .route("/healthz", get(handlers::health::healthz))
+        .route("/readyz", get(handlers::health::readyz))
         .route("/api/configs", get(handlers::config::get_configs))
         .route("/api/configs", post(handlers::config::save_config))
         .route("/api/configs/:name", delete(delete_config_handler))
         .route("/api/configs/:name/edit", get(get_config_for_edit_handler))
+        .route("/api/configs/:name/test", post(test_config_handler))
         .route("/api/configs/persist", post(handlers::config::persist_configs))
+        .route("/api/configs/backups", get(handlers::config::list_config_backups))
+        .route("/api/configs/backups/:id/restore", post(restore_config_backup_handler))
+        .route("/api/configs/sync-status", get(handlers::config::get_config_sync_status))
+        .route("/api/configs/resolve", post(handlers::config::resolve_config_conflict))
         .route("/api/files/local", get(handlers::files::list_local_files))
         .route("/api/files/remote", get(handlers::files::list_remote_files))
+        .route("/api/files/remote/mkdir", post(handlers::files::make_remote_directory))
+        .route("/api/files/remote/delete", post(handlers::files::delete_remote_path))
+        .route("/api/files/remote/move", post(handlers::files::move_remote_path))
+        .route("/api/files/remote/copy", post(handlers::files::copy_remote_path))
+        .route("/api/files/remote/download", get(handlers::files::download_remote_file))
+        .route("/api/files/remote/upload", post(handlers::files::upload_remote_file))
+        .route("/api/files/size", post(handlers::size::start_size_calculation))
+        .route("/api/files/size/:id", get(get_size_calculation_handler))
+        .route("/api/files/size/:id", delete(cancel_size_calculation_handler))
+        .route("/api/files/compare", get(handlers::compare::compare_directories))
         .route("/api/sync", post(handlers::sync::start_sync))
         .route("/api/sync", get(handlers::sync::list_sync_jobs))
         .route("/api/sync-log/:job_id", get(get_sync_log_handler))
         .route("/api/sync-delete/:job_id", delete(delete_sync_job_handler))
         .route("/api/sync/:job_id/log", get(get_sync_log_handler))
+        .route("/api/sync/:job_id/log/entries", get(get_sync_log_entries_handler))
+        .route("/api/sync/:job_id/errors", get(get_sync_errors_handler))
+        .route("/api/sync/:job_id/attempts", get(get_sync_attempts_handler))
+        .route("/api/sync/:job_id/retry-failed", post(retry_failed_files_handler))
+        .route("/api/sync/:job_id/resume", post(resume_job_handler))
+        .route("/api/sync/:job_id/bwlimit", get(get_job_bwlimit_handler))
+        .route("/api/sync/:job_id/bwlimit", post(set_job_bwlimit_handler))
         .route("/api/sync/:job_id", get(get_sync_progress_handler))
         .route("/api/sync/:job_id", delete(delete_sync_job_handler))
+        .route("/api/sync/:job_id/cancel", post(cancel_sync_job_handler))
+        .route("/api/jobs", get(handlers::sync::list_job_records))
+        .route("/api/stats/transfers", get(handlers::stats::get_transfer_history))
         .route("/api/tasks", get(handlers::tasks::get_tasks))
         .route("/api/tasks", post(handlers::tasks::create_task))
+        .route("/api/tasks/:task_id", put(handlers::tasks::update_task))
         .route("/api/tasks/:task_id", delete(handlers::tasks::delete_task))
         .route("/api/tasks/start", post(handlers::tasks::start_task))
+        .route("/api/tasks/:task_id/notifications", get(handlers::notifications::get_task_notifications))
+        .route("/api/tasks/:task_id/notifications", put(handlers::notifications::set_task_notifications))
+        .route("/api/notifications/channels", get(handlers::notifications::list_channels))
+        .route("/api/notifications/channels", post(handlers::notifications::create_channel))
+        .route("/api/notifications/channels/:id", put(handlers::notifications::update_channel))
+        .route("/api/notifications/channels/:id", delete(handlers::notifications::delete_channel))
+        .route("/api/notifications/channels/:id/test", post(handlers::notifications::test_channel))
+        .route("/api/bundle/export", post(handlers::bundle::export_bundle))
+        .route("/api/bundle/import", post(handlers::bundle::import_bundle))
         .nest_service("/static", ServeDir::new("static"))
         .layer(middleware::from_fn(request_logging_middleware))
         .layer(TraceLayer::new_for_http())
         .layer(Extension(config_manager))
         .layer(Extension(db_pool))
+        .layer(Extension(settings))
         .layer(
             ServiceBuilder::new()
                 .layer(CorsLayer::permissive())
@@ -148,28 +289,17 @@ async fn main() {
 
     let addr: SocketAddr = args.bind.parse().expect("Invalid bind address");
     
-    println!("🌐 Starting server...");
-    println!("   📍 Binding to: {}", addr);
-    println!("   🔗 URL: http://{}", addr);
-    println!("   📁 Serving static files from: ./static/");
-    println!("   📊 Request logging: enabled");
-    println!("");
+    info!("🌐 Starting server on {}, serving static files from ./static/", addr);
     
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    println!("✅ Server successfully started and listening on http://{}", addr);
-    println!("🎯 Ready to accept connections!");
-    println!("💡 Press Ctrl+C to stop the server");
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("");
+    info!("✅ Server listening on http://{}, press Ctrl+C to stop", addr);
     
     // Setup graceful shutdown
     let shutdown_signal = async {
         tokio::signal::ctrl_c()
             .await
             .expect("Failed to install CTRL+C signal handler");
-        println!("");
-        println!("🛑 Shutdown signal received");
-        println!("🔄 Gracefully shutting down server...");
+        info!("🛑 Shutdown signal received, shutting down gracefully");
     };
     
     // Run server with graceful shutdown
@@ -178,8 +308,7 @@ async fn main() {
         .await
         .unwrap();
         
-    println!("✅ Server shutdown completed");
-    println!("👋 Goodbye!");
+    info!("👋 Server shutdown completed");
 }
 
 async fn delete_config_handler(
@@ -189,16 +318,113 @@ async fn delete_config_handler(
     handlers::config::delete_config(Extension(config_manager), name).await
 }
 
-async fn get_sync_progress_handler(Path(job_id): Path<String>) -> axum::response::Json<models::ApiResponse<models::SyncProgress>> {
-    handlers::sync::get_sync_progress(job_id).await
+async fn restore_config_backup_handler(
+    Extension(config_manager): Extension<Arc<config_manager::ConfigManager>>,
+    Path(id): Path<String>,
+) -> axum::response::Json<models::ApiResponse<String>> {
+    handlers::config::restore_config_backup(Extension(config_manager), id).await
+}
+
+async fn get_sync_progress_handler(
+    Extension(settings): Extension<Arc<settings::Settings>>,
+    Path(job_id): Path<String>,
+) -> axum::response::Json<models::ApiResponse<models::SyncProgress>> {
+    handlers::sync::get_sync_progress(Extension(settings), job_id).await
 }
 
-async fn get_sync_log_handler(Path(job_id): Path<String>) -> axum::response::Json<models::ApiResponse<String>> {
-    handlers::sync::get_sync_log(job_id).await
+async fn get_sync_log_handler(
+    Extension(settings): Extension<Arc<settings::Settings>>,
+    Path(job_id): Path<String>,
+) -> axum::response::Json<models::ApiResponse<String>> {
+    handlers::sync::get_sync_log(Extension(settings), job_id).await
+}
+
+async fn get_sync_log_entries_handler(
+    Extension(settings): Extension<Arc<settings::Settings>>,
+    Path(job_id): Path<String>,
+    Query(query): Query<models::LogQuery>,
+) -> axum::response::Json<models::ApiResponse<models::LogPage>> {
+    handlers::sync::get_sync_log_entries(Extension(settings), job_id, query).await
 }
 
-async fn delete_sync_job_handler(Path(job_id): Path<String>) -> axum::response::Json<models::ApiResponse<String>> {
-    handlers::sync::delete_sync_job(job_id).await
+async fn get_sync_errors_handler(
+    Extension(pool): Extension<sqlx::Pool<sqlx::Sqlite>>,
+    Path(job_id): Path<String>,
+) -> axum::response::Json<models::ApiResponse<Vec<models::JobError>>> {
+    handlers::sync::get_sync_errors(Extension(pool), job_id).await
+}
+
+async fn get_sync_attempts_handler(
+    Extension(pool): Extension<sqlx::Pool<sqlx::Sqlite>>,
+    Path(job_id): Path<String>,
+) -> axum::response::Json<models::ApiResponse<Vec<models::JobAttempt>>> {
+    handlers::sync::get_sync_attempts(Extension(pool), job_id).await
+}
+
+async fn retry_failed_files_handler(
+    Extension(pool): Extension<sqlx::Pool<sqlx::Sqlite>>,
+    Extension(settings): Extension<Arc<settings::Settings>>,
+    Path(job_id): Path<String>,
+) -> axum::response::Json<models::ApiResponse<String>> {
+    handlers::sync::retry_failed_files(Extension(pool), Extension(settings), job_id).await
+}
+
+async fn resume_job_handler(
+    Extension(pool): Extension<sqlx::Pool<sqlx::Sqlite>>,
+    Extension(settings): Extension<Arc<settings::Settings>>,
+    Path(job_id): Path<String>,
+) -> axum::response::Json<models::ApiResponse<String>> {
+    handlers::sync::resume_job(Extension(pool), Extension(settings), job_id).await
+}
+
+async fn get_job_bwlimit_handler(
+    Extension(pool): Extension<sqlx::Pool<sqlx::Sqlite>>,
+    Path(job_id): Path<String>,
+) -> axum::response::Json<models::ApiResponse<models::BwLimit>> {
+    handlers::sync::get_job_bwlimit(Extension(pool), job_id).await
+}
+
+async fn set_job_bwlimit_handler(
+    Extension(pool): Extension<sqlx::Pool<sqlx::Sqlite>>,
+    Path(job_id): Path<String>,
+    axum::Json(request): axum::Json<models::BwLimitRequest>,
+) -> axum::response::Json<models::ApiResponse<models::BwLimit>> {
+    handlers::sync::set_job_bwlimit(Extension(pool), job_id, request).await
+}
+
+async fn delete_sync_job_handler(
+    Extension(pool): Extension<sqlx::Pool<sqlx::Sqlite>>,
+    Extension(settings): Extension<Arc<settings::Settings>>,
+    Path(job_id): Path<String>,
+) -> axum::response::Json<models::ApiResponse<String>> {
+    handlers::sync::delete_sync_job(Extension(pool), Extension(settings), job_id).await
+}
+
+async fn cancel_sync_job_handler(
+    Extension(pool): Extension<sqlx::Pool<sqlx::Sqlite>>,
+    Extension(settings): Extension<Arc<settings::Settings>>,
+    Path(job_id): Path<String>,
+) -> axum::response::Json<models::ApiResponse<String>> {
+    handlers::sync::cancel_sync_job(Extension(pool), Extension(settings), job_id).await
+}
+
+async fn test_config_handler(
+    Extension(config_manager): Extension<Arc<config_manager::ConfigManager>>,
+    Path(name): Path<String>,
+) -> axum::response::Json<models::ApiResponse<String>> {
+    handlers::config::test_config(Extension(config_manager), name).await
+}
+
+async fn get_size_calculation_handler(
+    Path(id): Path<String>,
+) -> axum::response::Json<models::ApiResponse<models::DirSize>> {
+    handlers::size::get_size_calculation(id).await
+}
+
+async fn cancel_size_calculation_handler(
+    Path(id): Path<String>,
+) -> axum::response::Json<models::ApiResponse<models::DirSize>> {
+    handlers::size::cancel_size_calculation(id).await
 }
 
 async fn get_config_for_edit_handler(
@@ -208,9 +434,19 @@ async fn get_config_for_edit_handler(
     handlers::config::get_config_for_edit(Extension(config_manager), name).await
 }
 
+/// Prometheus metrics in the text exposition format
+async fn serve_metrics(
+    Extension(pool): Extension<sqlx::Pool<sqlx::Sqlite>>,
+) -> ([(axum::http::HeaderName, &'static str); 1], String) {
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics::render(&pool).await,
+    )
+}
+
 async fn serve_index() -> Html<String> {
     let default_path = env::var("RCLONE_GUI_DEFAULT_PATH").unwrap_or_else(|_| "/mnt/home".to_string());
-    println!("🏠 Using default path: {}", default_path);
+    debug!("🏠 Using default path: {}", default_path);
     
     let html_content = std::fs::read_to_string("static/index.html")
         .unwrap_or_else(|_| include_str!("../static/index.html").to_string());
@@ -229,35 +465,29 @@ async fn serve_index() -> Html<String> {
     Html(modified_html)
 }
 
-/// Load environment configuration with detailed feedback
-fn load_environment_config() {
-    println!("🚀 Starting Rclone GUI...");
-    println!("📁 Working directory: {}", env::current_dir().unwrap_or_default().display());
-    println!("📋 Environment configuration:");
-
-    // Load .env file first
-    let env_loaded = match dotenv() {
-        Ok(_) => {
-            println!("   ✅ .env found and loaded");
-            true
-        }
-        Err(_) => {
-            println!("   ❌ .env not found");
-            false
-        }
-    };
+/// Load .env and .env.local (overrides .env) before the command line is parsed,
+/// so settings can be taken from them. Returns which of the files were found.
+fn load_environment_files() -> (bool, bool) {
+    let env_loaded = dotenv().is_ok();
+    let env_local_loaded = from_filename_override(".env.local").is_ok();
+    (env_loaded, env_local_loaded)
+}
 
-    // Load .env.local file (overrides .env)
-    let env_local_loaded = match from_filename_override(".env.local") {
-        Ok(_) => {
-            println!("   ✅ .env.local found and loaded (local overrides)");
-            true
-        }
-        Err(_) => {
-            println!("   ℹ️  .env.local not found (create from .env.local.example for local settings)");
-            false
-        }
-    };
+/// Log the environment configuration with detailed feedback
+fn report_environment_config(env_loaded: bool, env_local_loaded: bool) {
+    info!("🚀 Starting Rclone GUI in {}", env::current_dir().unwrap_or_default().display());
+
+    if env_loaded {
+        info!("✅ .env found and loaded");
+    } else {
+        info!("❌ .env not found");
+    }
+
+    if env_local_loaded {
+        info!("✅ .env.local found and loaded (local overrides)");
+    } else {
+        info!("ℹ️  .env.local not found (create from .env.local.example for local settings)");
+    }
 
     // Show current effective configuration
     let current_path = env::var("RCLONE_GUI_DEFAULT_PATH")
@@ -272,69 +502,40 @@ fn load_environment_config() {
         "fallback default"
     };
     
-    println!("   🎯 Active default path: {} ({})", current_path, source);
+    info!("🎯 Active default path: {} ({})", current_path, source);
 
     // Show other relevant environment variables
     if let Ok(rust_log) = env::var("RUST_LOG") {
-        println!("   🐛 Log level: {}", rust_log);
+        info!("🐛 Log level: {}", rust_log);
     }
     
     if let Ok(bind_addr) = env::var("RCLONE_GUI_BIND") {
-        println!("   🌐 Custom bind address: {}", bind_addr);
+        info!("🌐 Custom bind address: {}", bind_addr);
     }
-    
-    println!("");
 }
 
-/// Clean up any orphaned log files from previous application runs
-async fn cleanup_orphaned_log_files() {
-    use tokio::fs;
-    
-    let log_dir = "data/log";
-    
-    // Create log directory if it doesn't exist
-    if let Err(e) = fs::create_dir_all(log_dir).await {
-        eprintln!("Warning: Could not create log directory: {}", e);
-        return;
-    }
-    
-    match fs::read_dir(log_dir).await {
-        Ok(mut entries) => {
-            let mut removed_count = 0;
-            
-            while let Ok(Some(entry)) = entries.next_entry().await {
-                let path = entry.path();
-                
-                // Only remove .log files, keep .gitkeep and other files
-                if let Some(extension) = path.extension() {
-                    if extension == "log" {
-                        if let Err(e) = fs::remove_file(&path).await {
-                            eprintln!("Warning: Could not remove orphaned log file {:?}: {}", path, e);
-                        } else {
-                            removed_count += 1;
-                            println!("🧹 Removed orphaned log file: {:?}", path.file_name().unwrap_or_default());
-                        }
-                    }
-                }
-            }
-            
-            if removed_count > 0 {
-                println!("🗑️  Cleaned up {} orphaned log files from previous runs", removed_count);
-            } else {
-                println!("✅ No orphaned log files found");
-            }
-        }
-        Err(e) => {
-            eprintln!("Warning: Could not read log directory: {}", e);
-        }
-    }
-}
+/// Header carrying the ID of a request; an ID sent by a proxy is kept
+const REQUEST_ID_HEADER: &str = "x-request-id";
 
-/// Middleware function to log all HTTP requests
+/// Middleware function to log all HTTP requests.
+/// Every request gets an ID, attached to all events logged while handling it and returned in `x-request-id`.
 async fn request_logging_middleware(req: Request, next: Next) -> axum::response::Response {
     let method = req.method().clone();
     let uri = req.uri().clone();
     let headers = req.headers().clone();
+    let request_id = headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|hv| hv.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 128)
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let span = info_span!("request", request_id = %request_id, method = %method, uri = %uri);
+    // Route template for metrics, so path parameters do not create new series
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
     
     // Extract client IP (simplified)
     let client_ip = headers
@@ -345,15 +546,10 @@ async fn request_logging_middleware(req: Request, next: Next) -> axum::response:
     let start_time = std::time::Instant::now();
     
     // Log the incoming request
-    println!("📨 {} {} from {} at {}", 
-        method, 
-        uri, 
-        client_ip,
-        chrono::Utc::now().format("%H:%M:%S")
-    );
+    span.in_scope(|| info!(client_ip, "📨 {} {} from {}", method, uri, client_ip));
     
     // Process the request
-    let response = next.run(req).await;
+    let mut response = next.run(req).instrument(span.clone()).await;
     
     let duration = start_time.elapsed();
     let status = response.status();
@@ -367,127 +563,54 @@ async fn request_logging_middleware(req: Request, next: Next) -> axum::response:
         _ => "❓",
     };
     
-    println!("📤 {} {} {} - {}ms", 
+    span.in_scope(|| info!(
+        status = status.as_u16(),
+        duration_ms = duration.as_millis() as u64,
+        "📤 {} {} {} - {}ms",
         status_emoji,
         status.as_u16(), 
         uri,
         duration.as_millis()
-    );
+    ));
+
+    metrics::record_http_request(method.as_str(), &route, status.as_u16(), duration);
+
+    if let Ok(value) = request_id.parse() {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
     
     response
 }
 
-/// Setup enhanced tracing with environment-based filtering
-fn setup_tracing() {
+/// Setup enhanced tracing with environment-based filtering.
+/// CLI commands only log warnings by default and write them to stderr.
+fn setup_tracing(serving: bool, format: LogFormat) {
     // Default to INFO level, but allow override via RUST_LOG environment variable
     let filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("info"));
+        .unwrap_or_else(|_| EnvFilter::new(if serving { "info" } else { "warn" }));
     
-    tracing_subscriber::registry()
-        .with(filter)
-        .with(
-            tracing_subscriber::fmt::layer()
-                .with_target(false)
-                .with_file(false)
-                .with_line_number(false)
-                .compact()
-        )
-        .init();
-        
-    tracing::info!("🔍 Tracing initialized");
-}
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .with_file(false)
+        .with_line_number(false);
 
-/// Handle CLI task execution
-async fn handle_cli_task_execution(db_pool: sqlx::Pool<sqlx::Sqlite>, task_name: String) {
-    use crate::models::SyncRequest;
-    use axum::extract::Json;
-    
-    println!("🚀 Starting task '{}' from command line...", task_name);
-    
-    // Get task from database
-    let task = match database::get_task_by_name(&db_pool, &task_name).await {
-        Ok(Some(task)) => task,
-        Ok(None) => {
-            eprintln!("❌ Task '{}' not found", task_name);
-            std::process::exit(1);
-        }
-        Err(e) => {
-            eprintln!("❌ Failed to retrieve task '{}': {}", task_name, e);
-            std::process::exit(1);
-        }
-    };
-    
-    println!("📋 Task details:");
-    println!("   Name: {}", task.name);
-    println!("   Source: {}", task.source_path);
-    println!("   Remote: {}:{}", task.remote_name, task.remote_path);
-    println!("   Chunking: {}", if task.use_chunking { "enabled" } else { "disabled" });
-    if let Some(ref chunk_size) = task.chunk_size {
-        println!("   Chunk size: {}", chunk_size);
+    match format {
+        LogFormat::Text => tracing_subscriber::registry()
+            .with(filter)
+            .with(layer.compact())
+            .init(),
+        LogFormat::Json => tracing_subscriber::registry()
+            .with(filter)
+            .with(
+                layer
+                    .json()
+                    .flatten_event(true)
+                    .with_current_span(true)
+                    .with_span_list(false)
+            )
+            .init(),
     }
-    println!("");
-    
-    // Convert task to sync request
-    let sync_request = SyncRequest {
-        source_path: task.source_path,
-        remote_name: task.remote_name,
-        remote_path: task.remote_path,
-        chunk_size: task.chunk_size,
-        use_chunking: Some(task.use_chunking),
-    };
-    
-    // Start the sync job
-    let job_response = handlers::sync::start_sync(Json(sync_request)).await;
-    let job_id = match job_response.0.data {
-        Some(id) => id,
-        None => {
-            eprintln!("❌ Failed to start sync job: {:?}", job_response.0.error);
-            std::process::exit(1);
-        }
-    };
-    
-    println!("✅ Sync job started with ID: {}", job_id);
-    println!("📊 Monitoring progress...");
-    println!("");
-    
-    // Monitor progress
-    loop {
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
         
-        let progress_response = handlers::sync::get_sync_progress(job_id.clone()).await;
-        if let Some(progress) = progress_response.0.data {
-            println!("📈 Progress: {:.1}% | Status: {} | Transferred: {} / {}", 
-                progress.progress,
-                progress.status,
-                format_bytes(progress.transferred),
-                format_bytes(progress.total)
-            );
-            
-            if progress.status == "Completed" {
-                println!("");
-                println!("✅ Task '{}' completed successfully!", task_name);
-                break;
-            } else if progress.status == "Failed" || progress.status.contains("Error") {
-                println!("");
-                eprintln!("❌ Task '{}' failed: {}", task_name, progress.status);
-                std::process::exit(1);
-            }
-        }
-    }
+    debug!("🔍 Tracing initialized");
 }
-
-fn format_bytes(bytes: u64) -> String {
-    if bytes == 0 { return "0 B".to_string(); }
-    
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let base = 1024_f64;
-    let log = (bytes as f64).log(base).floor() as usize;
-    let unit_index = log.min(UNITS.len() - 1);
-    let value = bytes as f64 / base.powi(unit_index as i32);
-    
-    if unit_index >= 3 {
-        format!("{:.1} {}", value, UNITS[unit_index])
-    } else {
-        format!("{:.0} {}", value, UNITS[unit_index])
-    }
-}
\ No newline at end of file
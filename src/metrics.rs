@@ -0,0 +1,121 @@
+use prometheus::{
+    Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use sqlx::{Pool, Sqlite};
+use std::time::Duration;
+use tracing::error;
+use crate::database;
+use crate::handlers::sync;
+use crate::models::{SyncProgress, SyncRequest};
+
+lazy_static::lazy_static! {
+    static ref REGISTRY: Registry = Registry::new_custom(Some("rclone_gui".to_string()), None)
+        .expect("valid metrics prefix");
+
+    static ref HTTP_REQUESTS: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("http_requests_total", "HTTP requests by method, route and status"),
+        &["method", "route", "status"],
+    ));
+    static ref HTTP_DURATION: HistogramVec = register(HistogramVec::new(
+        HistogramOpts::new("http_request_duration_seconds", "HTTP request latency by method, route and status"),
+        &["method", "route", "status"],
+    ));
+    static ref TRANSFERRED_BYTES: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("transferred_bytes_total", "Bytes transferred by finished jobs, by task and remote"),
+        &["task", "remote"],
+    ));
+    static ref SPAWN_FAILURES: IntCounter = register(IntCounter::new(
+        "rclone_spawn_failures_total",
+        "Jobs whose rclone process could not be started",
+    ));
+    static ref JOBS: IntGaugeVec = register(IntGaugeVec::new(
+        Opts::new("jobs", "Stored jobs by state"),
+        &["state"],
+    ));
+    static ref QUEUE_DEPTH: IntGauge = register(IntGauge::new(
+        "queue_depth",
+        "Jobs registered but not yet running",
+    ));
+    static ref LAST_SUCCESS: GaugeVec = register(GaugeVec::new(
+        Opts::new("task_last_success_timestamp_seconds", "End time of the last completed job of each task"),
+        &["task"],
+    ));
+}
+
+fn register<T: prometheus::core::Collector + Clone + 'static>(metric: prometheus::Result<T>) -> T {
+    let metric = metric.expect("valid metric definition");
+    REGISTRY.register(Box::new(metric.clone())).expect("metric registered once");
+    metric
+}
+
+/// Record a handled HTTP request. `route` is the matched route template, not the request path.
+pub fn record_http_request(method: &str, route: &str, status: u16, duration: Duration) {
+    let status = status.to_string();
+    let labels = [method, route, status.as_str()];
+    HTTP_REQUESTS.with_label_values(&labels).inc();
+    HTTP_DURATION.with_label_values(&labels).observe(duration.as_secs_f64());
+}
+
+/// Record the final state of a job
+pub fn record_job_finished(request: &SyncRequest, progress: &SyncProgress) {
+    if sync::is_spawn_failure(&progress.status) {
+        SPAWN_FAILURES.inc();
+    }
+    TRANSFERRED_BYTES
+        .with_label_values(&[request.task_name.as_deref().unwrap_or(""), &request.remote_name])
+        .inc_by(progress.transferred);
+}
+
+/// Metric state of a job status
+fn job_state(status: &str) -> &'static str {
+    match status {
+        "Starting" => "starting",
+        "Running" => "running",
+        "Cancelling" => "cancelling",
+        "Completed" => "completed",
+        "Cancelled" => "cancelled",
+        "Interrupted" | "Resuming" => "interrupted",
+        s if s.starts_with("Resumed") => "interrupted",
+        "Retrying" => "retrying",
+        s if s.starts_with("Failed") || s.starts_with("Error") => "failed",
+        _ => "other",
+    }
+}
+
+/// Render all metrics in the Prometheus text format. Job gauges are read from the database.
+pub async fn render(pool: &Pool<Sqlite>) -> String {
+    // Metrics register on first use, plain counters should be exported from the start
+    lazy_static::initialize(&SPAWN_FAILURES);
+
+    match database::count_jobs_by_status(pool).await {
+        Ok(counts) => {
+            JOBS.reset();
+            QUEUE_DEPTH.set(0);
+            for (status, count) in counts {
+                let state = job_state(&status);
+                JOBS.with_label_values(&[state]).add(count);
+                if state == "starting" {
+                    QUEUE_DEPTH.add(count);
+                }
+            }
+        }
+        Err(e) => error!("Failed to count jobs for metrics: {}", e),
+    }
+
+    match database::get_last_task_successes(pool).await {
+        Ok(successes) => {
+            LAST_SUCCESS.reset();
+            for (task, end_time) in successes {
+                LAST_SUCCESS.with_label_values(&[&task]).set(end_time as f64);
+            }
+        }
+        Err(e) => error!("Failed to read last task successes for metrics: {}", e),
+    }
+
+    let mut buffer = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&REGISTRY.gather(), &mut buffer) {
+        error!("Failed to encode metrics: {}", e);
+    }
+    String::from_utf8(buffer).unwrap_or_default()
+}
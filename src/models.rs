@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use sqlx::FromRow;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RcloneConfig {
     pub name: String,
     pub config_type: String,
@@ -18,16 +18,275 @@ pub struct FileEntry {
     pub path: String,
     pub is_dir: bool,
     pub size: Option<u64>,
+    /// RFC 3339 timestamp in UTC, for local and remote entries alike
     pub modified: Option<String>,
+    pub mime_type: Option<String>,
+    /// Target of a symbolic link (local only)
+    pub symlink_target: Option<String>,
+    /// Unix permissions such as `rwxr-xr-x` (local only)
+    pub permissions: Option<String>,
+    pub owner: Option<String>,
+    pub group: Option<String>,
+    /// Hashes by algorithm (e.g. md5, sha1), only when requested with `hash=true`
+    pub hashes: Option<HashMap<String, String>>,
+}
+
+/// Path on a remote, used by the remote file operations
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemotePathRequest {
+    pub remote: String,
+    pub path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemoteDeleteRequest {
+    pub remote: String,
+    pub path: String,
+    /// Delete a directory with all its contents (`rclone purge`) instead of a single file
+    #[serde(default)]
+    pub purge: bool,
+    /// Must repeat `path` to confirm the deletion
+    pub confirm: String,
+}
+
+/// Calculate the total size of a local directory (no `remote`) or a remote path
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SizeRequest {
+    pub remote: Option<String>,
+    pub path: String,
+    /// Ignore a cached result and calculate again
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// Result (or progress) of a directory size calculation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirSize {
+    pub id: String,
+    pub remote: Option<String>,
+    pub path: String,
+    /// Running, Completed, Cancelled or Failed: <reason>
+    pub status: String,
+    pub bytes: u64,
+    pub files: u64,
+    /// Number of subdirectories (local only, rclone size does not report them)
+    pub dirs: Option<u64>,
+    /// Objects without a known size (remote only)
+    pub sizeless: Option<u64>,
+    /// Entries that could not be read (local only)
+    pub errors: Option<u64>,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+/// Compare a local directory with a remote path, recursively
+#[derive(Debug, Deserialize)]
+pub struct CompareRequest {
+    pub local_path: String,
+    pub remote: String,
+    pub remote_path: String,
+    /// Compare hashes of files with equal size (md5/sha1, computed locally)
+    #[serde(default)]
+    pub hash: bool,
+    /// Only return entries in these states (comma separated)
+    pub states: Option<String>,
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+    /// Ignore a cached comparison and compare again
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareState {
+    OnlyLocal,
+    OnlyRemote,
+    Identical,
+    DiffersSize,
+    DiffersHash,
+    DiffersTime,
+}
+
+/// One path of a directory comparison, relative to the compared directories
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareEntry {
+    pub path: String,
+    pub is_dir: bool,
+    pub state: CompareState,
+    pub local_size: Option<u64>,
+    pub remote_size: Option<u64>,
+    pub local_modified: Option<String>,
+    pub remote_modified: Option<String>,
+}
+
+/// A page of a directory comparison with the number of entries per state
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DirComparison {
+    pub local_path: String,
+    pub remote: String,
+    pub remote_path: String,
+    pub compared_at: i64,
+    pub summary: HashMap<CompareState, usize>,
+    /// Number of entries matching `states`, before pagination
+    pub total: usize,
+    pub entries: Vec<CompareEntry>,
+}
+
+/// Dimension the transfer history is split into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsGroupBy {
+    #[default]
+    Task,
+    Remote,
+    None,
+}
+
+/// Length of the periods of the transfer history, in UTC
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsInterval {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+/// Select the finished jobs aggregated into the transfer history
+#[derive(Debug, Default, Deserialize)]
+pub struct StatsQuery {
+    #[serde(default)]
+    pub group_by: StatsGroupBy,
+    #[serde(default)]
+    pub interval: StatsInterval,
+    /// Start of the history [default: 30 days ago]
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// End of the history [default: now]
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only jobs of this task
+    pub task: Option<String>,
+    /// Only jobs to this remote
+    pub remote: Option<String>,
+}
+
+/// Aggregated finished jobs of one period, or of a whole series
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransferStats {
+    pub jobs: i64,
+    pub succeeded: i64,
+    pub failed: i64,
+    /// Completed jobs among completed and failed ones, cancelled jobs are not counted
+    pub success_rate: Option<f64>,
+    pub bytes: i64,
+    pub files: i64,
+    pub duration_secs: i64,
+    pub avg_duration_secs: Option<f64>,
+    /// Bytes per second over the jobs' run time
+    pub avg_throughput: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsPoint {
+    /// `2024-05-31` (day), `2024-W22` (week starting Monday) or `2024-05` (month)
+    pub period: String,
+    #[serde(flatten)]
+    pub stats: TransferStats,
+}
+
+/// History of one task or remote (`key` is null for jobs without a task, and with `group_by=none`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSeries {
+    pub key: Option<String>,
+    pub totals: TransferStats,
+    pub points: Vec<StatsPoint>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransferHistory {
+    pub group_by: StatsGroupBy,
+    pub interval: StatsInterval,
+    pub since: chrono::DateTime<chrono::Utc>,
+    pub until: chrono::DateTime<chrono::Utc>,
+    pub series: Vec<StatsSeries>,
 }
 
+/// Move/rename or copy a file or directory, also between different remotes
 #[derive(Debug, Serialize, Deserialize)]
+pub struct RemoteTransferRequest {
+    pub src_remote: String,
+    pub src_path: String,
+    pub dst_remote: String,
+    pub dst_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncRequest {
     pub source_path: String,
     pub remote_name: String,
     pub remote_path: String,
     pub chunk_size: Option<String>,  // z.B. "8M", "16M", "32M"
     pub use_chunking: Option<bool>,
+    #[serde(default)]
+    pub task_name: Option<String>,
+    /// Only copy these paths, relative to `source_path` (rclone `--files-from`)
+    #[serde(default)]
+    pub files_from: Option<Vec<String>>,
+    /// Bandwidth limit, a rate or timetable; the global limit applies if unset
+    #[serde(default)]
+    pub bwlimit: Option<String>,
+    /// Run rclone again when it fails as a whole, e.g. while the network is down
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+}
+
+/// Retries of a job whose rclone process failed. All attempts belong to the same job and log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Attempts including the first one
+    pub max_attempts: u32,
+    /// Delays before the retries in seconds; the last one is used for all further retries
+    #[serde(default = "default_retry_backoff")]
+    pub backoff_secs: Vec<u64>,
+    /// rclone exit codes that are retried
+    #[serde(default = "default_retry_exit_codes")]
+    pub exit_codes: Vec<i32>,
+}
+
+fn default_retry_backoff() -> Vec<u64> {
+    vec![60, 300, 1800]
+}
+
+/// rclone's uncategorised (1) and temporary (5) errors
+fn default_retry_exit_codes() -> Vec<i32> {
+    vec![1, 5]
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32) -> Self {
+        Self { max_attempts, backoff_secs: default_retry_backoff(), exit_codes: default_retry_exit_codes() }
+    }
+
+    /// Delay before the given attempt, `None` if the exit code is not retried or all attempts are used
+    pub fn delay_before(&self, attempt: u32, exit_code: i32) -> Option<u64> {
+        if attempt > self.max_attempts || !self.exit_codes.contains(&exit_code) {
+            return None;
+        }
+        let index = (attempt as usize).saturating_sub(2).min(self.backoff_secs.len().saturating_sub(1));
+        Some(self.backoff_secs.get(index).copied().unwrap_or(0))
+    }
+}
+
+/// One run of rclone for a job
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct JobAttempt {
+    /// Counted from 1
+    pub attempt: u32,
+    pub status: String,
+    pub exit_code: Option<i64>,
+    pub start_time: i64,
+    pub end_time: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +299,175 @@ pub struct SyncProgress {
     pub source_name: String,
     pub start_time: i64,
     pub end_time: Option<i64>,
+    /// Exit code of the rclone process once it has exited
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Files transferred, from rclone's final stats
+    #[serde(default)]
+    pub files: u64,
+    /// Current attempt of the job's retry policy
+    #[serde(default = "first_attempt")]
+    pub attempt: u32,
+}
+
+fn first_attempt() -> u32 {
+    1
+}
+
+/// Change the bandwidth limit of a running job
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BwLimitRequest {
+    /// A single rate, e.g. `1M`, `512k:2M` (upload:download) or `off`
+    pub rate: String,
+}
+
+/// Current bandwidth limit of a running job as reported by rclone
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BwLimit {
+    pub rate: String,
+    #[serde(rename(deserialize = "bytesPerSecond"))]
+    pub bytes_per_second: i64,
+}
+
+/// Persisted record of a sync job, shared between the server and CLI processes
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct JobRecord {
+    pub id: String,
+    pub task_name: Option<String>,
+    pub source_path: String,
+    pub remote_name: String,
+    pub remote_path: String,
+    pub status: String,
+    pub progress: f64,
+    pub transferred: i64,
+    pub total: i64,
+    pub pid: Option<i64>,
+    pub exit_code: Option<i64>,
+    pub start_time: i64,
+    pub end_time: Option<i64>,
+    #[serde(default)]
+    pub files: i64,
+    /// Port of the rclone remote control server of a running job
+    #[serde(skip_serializing, default)]
+    pub rc_port: Option<i64>,
+    #[serde(skip_serializing, default)]
+    pub rc_password: Option<String>,
+    #[serde(default = "first_attempt")]
+    pub attempt: u32,
+}
+
+/// A file that rclone failed to transfer in a job
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct JobError {
+    /// Path relative to the job's source and destination
+    pub object: String,
+    /// Last error reported for the file
+    pub message: String,
+    /// How often rclone retried the file after its first error
+    pub retries: u32,
+}
+
+/// Severity of a job log entry, in rclone's order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    #[serde(alias = "warn")]
+    Warning,
+    Error,
+    #[serde(alias = "alert", alias = "emergency")]
+    Critical,
+}
+
+/// Filter and page the entries of a job log
+#[derive(Debug, Default, Deserialize)]
+pub struct LogQuery {
+    /// Only entries of this level or more severe
+    pub level: Option<LogLevel>,
+    /// Only entries whose object (file) contains this text (case-insensitive)
+    pub object: Option<String>,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+    /// Return the last `tail` matching entries instead of paging from `offset`
+    pub tail: Option<usize>,
+    /// Include rclone's periodic stats entries
+    #[serde(default)]
+    pub stats: bool,
+}
+
+/// One line of a job log, parsed from rclone's `--use-json-log` output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Line number in the log file, starting at 1
+    pub line: usize,
+    pub time: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub level: LogLevel,
+    pub object: Option<String>,
+    pub msg: String,
+    pub stats: Option<serde_json::Value>,
+}
+
+/// A page of the entries of a job log matching a query
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogPage {
+    pub job_id: String,
+    /// Number of matching entries, before pagination
+    pub total: usize,
+    /// Position of the first returned entry among the matching entries
+    pub offset: usize,
+    pub entries: Vec<LogEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBackup {
+    pub id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSyncState {
+    InSync,
+    MemoryChanged,
+    DiskChanged,
+    Conflict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    Memory,
+    Disk,
+}
+
+/// One remote that differs between the last loaded file, memory and the file on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteDiff {
+    pub name: String,
+    pub base: Option<RcloneConfig>,
+    pub memory: Option<RcloneConfig>,
+    pub disk: Option<RcloneConfig>,
+    pub memory_changed: bool,
+    pub disk_changed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigSyncStatus {
+    pub state: ConfigSyncState,
+    pub remotes: Vec<RemoteDiff>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolveConflictRequest {
+    #[serde(default)]
+    pub choices: HashMap<String, ConfigSource>,
+    pub default: Option<ConfigSource>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,6 +496,15 @@ pub struct Task {
     pub remote_path: String,
     pub chunk_size: Option<String>,
     pub use_chunking: bool,
+    /// Bandwidth limit overriding the global one, a rate or timetable (rclone `--bwlimit`)
+    #[serde(default)]
+    pub bwlimit: Option<String>,
+    /// Restart jobs of this task that were interrupted by a server restart
+    #[serde(default)]
+    pub resume_interrupted: bool,
+    #[serde(default)]
+    #[sqlx(json(nullable))]
+    pub retry: Option<RetryPolicy>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
@@ -79,6 +516,12 @@ pub struct TaskRequest {
     pub remote_path: String,
     pub chunk_size: Option<String>,
     pub use_chunking: Option<bool>,
+    #[serde(default)]
+    pub bwlimit: Option<String>,
+    #[serde(default)]
+    pub resume_interrupted: Option<bool>,
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -86,6 +529,154 @@ pub struct StartTaskRequest {
     pub task_name: String,
 }
 
+/// Delivery settings of a notification channel, stored as JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ChannelConfig {
+    /// POST of the JSON payload
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+    /// Plain text mail over SMTP
+    Email {
+        host: String,
+        port: Option<u16>,
+        #[serde(default)]
+        security: SmtpSecurity,
+        username: Option<String>,
+        password: Option<String>,
+        from: String,
+        to: Vec<String>,
+    },
+    /// Push message to an ntfy topic
+    Ntfy {
+        #[serde(default = "default_ntfy_url")]
+        url: String,
+        topic: String,
+        token: Option<String>,
+        priority: Option<u8>,
+    },
+    /// Push message to a Gotify server
+    Gotify {
+        url: String,
+        token: String,
+        priority: Option<u8>,
+    },
+}
+
+fn default_ntfy_url() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+/// Shown instead of channel passwords, tokens and header values; sending it back keeps the stored value
+pub const SECRET_PLACEHOLDER: &str = "***";
+
+fn restore_secret(value: &mut String, stored: Option<&String>) {
+    if let (true, Some(stored)) = (value == SECRET_PLACEHOLDER, stored) {
+        *value = stored.clone();
+    }
+}
+
+impl ChannelConfig {
+    /// Copy with every secret replaced by `SECRET_PLACEHOLDER`, for API responses
+    pub fn redacted(&self) -> Self {
+        let hide = |_: &String| SECRET_PLACEHOLDER.to_string();
+        let mut config = self.clone();
+        match &mut config {
+            ChannelConfig::Webhook { headers, .. } => headers.values_mut().for_each(|v| *v = hide(v)),
+            ChannelConfig::Email { password, .. } => *password = password.as_ref().map(hide),
+            ChannelConfig::Ntfy { token, .. } => *token = token.as_ref().map(hide),
+            ChannelConfig::Gotify { token, .. } => *token = hide(token),
+        }
+        config
+    }
+
+    /// Replace placeholders sent back by a client with the secrets of the stored configuration
+    pub fn restore_secrets(&mut self, stored: &ChannelConfig) {
+        match (self, stored) {
+            (ChannelConfig::Webhook { headers, .. }, ChannelConfig::Webhook { headers: stored, .. }) => {
+                for (name, value) in headers.iter_mut() {
+                    restore_secret(value, stored.get(name));
+                }
+            }
+            (ChannelConfig::Email { password: Some(value), .. }, ChannelConfig::Email { password: stored, .. }) => {
+                restore_secret(value, stored.as_ref())
+            }
+            (ChannelConfig::Ntfy { token: Some(value), .. }, ChannelConfig::Ntfy { token: stored, .. }) => {
+                restore_secret(value, stored.as_ref())
+            }
+            (ChannelConfig::Gotify { token: value, .. }, ChannelConfig::Gotify { token: stored, .. }) => {
+                restore_secret(value, Some(stored))
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    #[default]
+    Starttls,
+    Tls,
+    None,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationChannel {
+    pub id: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub config: ChannelConfig,
+    pub enabled: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationChannelRequest {
+    pub name: String,
+    #[serde(flatten)]
+    pub config: ChannelConfig,
+    pub enabled: Option<bool>,
+}
+
+/// Which job results of a task are sent to a channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyOn {
+    Failure,
+    Success,
+    Always,
+}
+
+impl NotifyOn {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotifyOn::Failure => "failure",
+            NotifyOn::Success => "success",
+            NotifyOn::Always => "always",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "failure" => Some(NotifyOn::Failure),
+            "success" => Some(NotifyOn::Success),
+            "always" => Some(NotifyOn::Always),
+            _ => None,
+        }
+    }
+}
+
+/// Subscription of a task to a notification channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskNotification {
+    pub channel_id: String,
+    pub notify_on: NotifyOn,
+}
+
 impl<T> ApiResponse<T> {
     pub fn success(data: T) -> Self {
         Self {
@@ -102,4 +693,86 @@ impl<T> ApiResponse<T> {
             error: Some(message.to_string()),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_wait_for_their_backoff() {
+        let retry = RetryPolicy { max_attempts: 6, backoff_secs: vec![10, 20, 30], exit_codes: vec![1, 5] };
+
+        // The first retry is attempt 2 and waits for the first delay
+        assert_eq!(retry.delay_before(2, 1), Some(10));
+        assert_eq!(retry.delay_before(3, 5), Some(20));
+        assert_eq!(retry.delay_before(4, 1), Some(30));
+        // The last delay is reused
+        assert_eq!(retry.delay_before(5, 1), Some(30));
+        assert_eq!(retry.delay_before(6, 1), Some(30));
+        assert_eq!(retry.delay_before(7, 1), None);
+    }
+
+    #[test]
+    fn retries_follow_exit_codes() {
+        let retry = RetryPolicy::new(3);
+
+        assert_eq!(retry.delay_before(2, 1), Some(60));
+        assert_eq!(retry.delay_before(3, 5), Some(300));
+        assert_eq!(retry.delay_before(2, 2), None);
+        assert_eq!(retry.delay_before(2, 0), None);
+    }
+
+    #[test]
+    fn retries_without_backoff_start_at_once() {
+        let retry = RetryPolicy { max_attempts: 3, backoff_secs: vec![], exit_codes: vec![1] };
+
+        assert_eq!(retry.delay_before(2, 1), Some(0));
+        assert_eq!(retry.delay_before(3, 1), Some(0));
+    }
+
+    fn webhook(headers: &[(&str, &str)]) -> ChannelConfig {
+        ChannelConfig::Webhook {
+            url: "https://example.com/hook".to_string(),
+            headers: headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn channel_secrets_are_redacted() {
+        let redacted = webhook(&[("Authorization", "Bearer secret")]).redacted();
+        assert!(matches!(redacted, ChannelConfig::Webhook { ref headers, .. } if headers["Authorization"] == SECRET_PLACEHOLDER));
+
+        let email = ChannelConfig::Email {
+            host: "smtp.example.com".to_string(),
+            port: None,
+            security: SmtpSecurity::default(),
+            username: Some("backup".to_string()),
+            password: Some("hunter2".to_string()),
+            from: "backup@example.com".to_string(),
+            to: vec!["ops@example.com".to_string()],
+        };
+        let ChannelConfig::Email { username, password, .. } = email.redacted() else { panic!("type changed") };
+        assert_eq!(username.as_deref(), Some("backup"));
+        assert_eq!(password.as_deref(), Some(SECRET_PLACEHOLDER));
+
+        let ntfy = ChannelConfig::Ntfy { url: default_ntfy_url(), topic: "backups".to_string(), token: None, priority: None };
+        assert!(matches!(ntfy.redacted(), ChannelConfig::Ntfy { token: None, .. }));
+    }
+
+    #[test]
+    fn placeholders_keep_stored_secrets() {
+        let stored = webhook(&[("Authorization", "Bearer secret"), ("X-Key", "old")]);
+        let mut update = webhook(&[("Authorization", SECRET_PLACEHOLDER), ("X-Key", "new"), ("X-Other", SECRET_PLACEHOLDER)]);
+        update.restore_secrets(&stored);
+
+        let ChannelConfig::Webhook { headers, .. } = update else { panic!("type changed") };
+        assert_eq!(headers["Authorization"], "Bearer secret");
+        assert_eq!(headers["X-Key"], "new");
+        assert_eq!(headers["X-Other"], SECRET_PLACEHOLDER);
+
+        let stored = ChannelConfig::Gotify { url: "https://gotify".to_string(), token: "abc".to_string(), priority: None };
+        let mut update = stored.redacted();
+        update.restore_secrets(&stored);
+        assert!(matches!(update, ChannelConfig::Gotify { ref token, .. } if token == "abc"));
+    }
+}
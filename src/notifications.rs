@@ -0,0 +1,293 @@
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::task::TaskTracker;
+use tracing::{info, warn, Instrument};
+use crate::database;
+use crate::joblogs;
+use crate::models::{ChannelConfig, NotificationChannel, NotifyOn, SmtpSecurity, SyncProgress, SyncRequest};
+use crate::settings::Settings;
+
+/// Number of log lines included in a notification
+const LOG_TAIL_LINES: usize = 20;
+
+/// Timeout of a single delivery
+const SEND_TIMEOUT: Duration = Duration::from_secs(15);
+
+lazy_static::lazy_static! {
+    static ref PENDING: TaskTracker = TaskTracker::new();
+    static ref HTTP: reqwest::Client = reqwest::Client::builder()
+        .timeout(SEND_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+}
+
+/// Payload sent when a task's job has finished. Webhooks receive it as JSON,
+/// the other channels a text rendering of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobNotification {
+    /// `job.finished`, or `test` for test messages
+    pub event: String,
+    pub task_name: String,
+    pub job_id: String,
+    pub status: String,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub source_path: String,
+    pub destination: String,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+    pub duration_secs: Option<i64>,
+    pub transferred: u64,
+    pub total: u64,
+    /// Last stats block rclone wrote to the job log (bytes, errors, transfers, ...)
+    pub stats: Option<serde_json::Value>,
+    pub log_tail: Vec<String>,
+}
+
+impl JobNotification {
+    fn title(&self) -> String {
+        match self.status.as_str() {
+            "Completed" => format!("Task '{}' completed", self.task_name),
+            "Cancelled" => format!("Task '{}' was cancelled", self.task_name),
+            status => format!("Task '{}' failed: {}", self.task_name, status),
+        }
+    }
+
+    /// Title with a status emoji, for channels without tag support
+    fn title_with_icon(&self) -> String {
+        let icon = match self.status.as_str() {
+            "Completed" => "✅",
+            "Cancelled" => "🛑",
+            _ => "❌",
+        };
+        format!("{} {}", icon, self.title())
+    }
+
+    /// ntfy tag rendered as an emoji next to the title
+    fn ntfy_tag(&self) -> &'static str {
+        match self.status.as_str() {
+            "Completed" => "white_check_mark",
+            "Cancelled" => "stop_sign",
+            _ => "x",
+        }
+    }
+
+    fn text(&self) -> String {
+        let mut text = format!(
+            "Job: {}\nSource: {}\nDestination: {}\nStatus: {}\n",
+            self.job_id, self.source_path, self.destination, self.status
+        );
+        if let Some(code) = self.exit_code {
+            text.push_str(&format!("rclone exit code: {}\n", code));
+        }
+        if let Some(duration) = self.duration_secs {
+            text.push_str(&format!("Duration: {}s\n", duration));
+        }
+        text.push_str(&format!("Transferred: {} / {} bytes\n", self.transferred, self.total));
+        if let Some(stats) = &self.stats {
+            text.push_str(&format!(
+                "Files: {} transferred, {} checked, {} errors\n",
+                stats["transfers"].as_u64().unwrap_or(0),
+                stats["checks"].as_u64().unwrap_or(0),
+                stats["errors"].as_u64().unwrap_or(0),
+            ));
+        }
+        if !self.log_tail.is_empty() {
+            text.push_str("\nLog:\n");
+            text.push_str(&self.log_tail.join("\n"));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Example payload used to test a channel
+    pub fn test() -> Self {
+        let now = chrono::Utc::now().timestamp();
+        Self {
+            event: "test".to_string(),
+            task_name: "example".to_string(),
+            job_id: "00000000-0000-0000-0000-000000000000".to_string(),
+            status: "Completed".to_string(),
+            success: true,
+            exit_code: Some(0),
+            source_path: "/mnt/home/example".to_string(),
+            destination: "remote:/example".to_string(),
+            started_at: now,
+            finished_at: Some(now),
+            duration_secs: Some(0),
+            transferred: 0,
+            total: 0,
+            stats: None,
+            log_tail: vec!["This is a test notification from rclone-gui".to_string()],
+        }
+    }
+}
+
+fn wants(notify_on: NotifyOn, success: bool) -> bool {
+    match notify_on {
+        NotifyOn::Always => true,
+        NotifyOn::Success => success,
+        NotifyOn::Failure => !success,
+    }
+}
+
+/// Notify the channels a finished job's task is subscribed to, in the background.
+/// Jobs without a task are ignored. Cancelled jobs only notify `always` subscriptions.
+pub fn job_finished(pool: &Pool<Sqlite>, settings: &Arc<Settings>, request: &SyncRequest, progress: &SyncProgress) {
+    let Some(task_name) = request.task_name.clone() else {
+        return;
+    };
+    let (pool, settings, request, progress) = (pool.clone(), settings.clone(), request.clone(), progress.clone());
+
+    PENDING.spawn(async move {
+        let channels = match database::get_subscribed_channels(&pool, &task_name).await {
+            Ok(channels) => channels,
+            Err(e) => {
+                warn!("🔔 Could not load notification channels of task '{}': {}", task_name, e);
+                return;
+            }
+        };
+
+        let success = progress.status == "Completed";
+        let cancelled = progress.status == "Cancelled";
+        let channels: Vec<NotificationChannel> = channels
+            .into_iter()
+            .filter(|(_, notify_on)| if cancelled { *notify_on == NotifyOn::Always } else { wants(*notify_on, success) })
+            .map(|(channel, _)| channel)
+            .collect();
+        if channels.is_empty() {
+            return;
+        }
+
+        let (stats, log_tail) = read_log_summary(&settings, &progress.id).await;
+        // The job's own counters are only updated while someone polls its progress
+        let stat = |key: &str, fallback: u64| {
+            stats.as_ref().and_then(|s| s[key].as_u64()).unwrap_or(fallback)
+        };
+        let notification = JobNotification {
+            event: "job.finished".to_string(),
+            task_name,
+            job_id: progress.id.clone(),
+            status: progress.status.clone(),
+            success,
+            exit_code: progress.exit_code,
+            source_path: request.source_path.clone(),
+            destination: format!("{}:{}", request.remote_name, request.remote_path),
+            started_at: progress.start_time,
+            finished_at: progress.end_time,
+            duration_secs: progress.end_time.map(|end| end - progress.start_time),
+            transferred: stat("bytes", progress.transferred),
+            total: stat("totalBytes", progress.total),
+            stats,
+            log_tail,
+        };
+
+        let deliveries = channels.iter().map(|channel| async {
+            match send(channel, &notification).await {
+                Ok(()) => info!("🔔 Notified '{}' about job {}", channel.name, notification.job_id),
+                Err(e) => warn!("🔔 Notification to '{}' failed: {}", channel.name, e),
+            }
+        });
+        futures::future::join_all(deliveries).await;
+    }.in_current_span());
+}
+
+/// Wait for notifications still being delivered, before a CLI run exits
+pub async fn wait_pending() {
+    PENDING.close();
+    PENDING.wait().await;
+}
+
+/// Last rclone stats block and the last log lines (without stats lines) of a job
+async fn read_log_summary(settings: &Settings, job_id: &str) -> (Option<serde_json::Value>, Vec<String>) {
+    let Ok(content) = joblogs::read(settings, job_id).await else {
+        return (None, Vec::new());
+    };
+
+    let mut stats = None;
+    let mut lines = Vec::new();
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(json) if json.get("stats").is_some() => stats = json.get("stats").cloned(),
+            Ok(json) => lines.push(format!(
+                "{} {} {}{}",
+                json["time"].as_str().unwrap_or(""),
+                json["level"].as_str().unwrap_or("").to_uppercase(),
+                json["object"].as_str().map(|o| format!("{}: ", o)).unwrap_or_default(),
+                json["msg"].as_str().unwrap_or("").trim(),
+            )),
+            Err(_) => lines.push(line.to_string()),
+        }
+    }
+
+    let tail = lines.split_off(lines.len().saturating_sub(LOG_TAIL_LINES));
+    (stats, tail)
+}
+
+/// Deliver a notification to one channel
+pub async fn send(channel: &NotificationChannel, notification: &JobNotification) -> anyhow::Result<()> {
+    match &channel.config {
+        ChannelConfig::Webhook { url, headers } => {
+            let mut request = HTTP.post(url).json(notification);
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            request.send().await?.error_for_status()?;
+        }
+        ChannelConfig::Ntfy { url, topic, token, priority } => {
+            let mut request = HTTP
+                .post(format!("{}/{}", url.trim_end_matches('/'), topic))
+                .header("Title", notification.title())
+                .header("Tags", notification.ntfy_tag())
+                .body(notification.text());
+            if let Some(priority) = priority {
+                request = request.header("Priority", priority.to_string());
+            }
+            if let Some(token) = token {
+                request = request.bearer_auth(token);
+            }
+            request.send().await?.error_for_status()?;
+        }
+        ChannelConfig::Gotify { url, token, priority } => {
+            HTTP.post(format!("{}/message", url.trim_end_matches('/')))
+                .header("X-Gotify-Key", token)
+                .json(&serde_json::json!({
+                    "title": notification.title_with_icon(),
+                    "message": notification.text(),
+                    "priority": priority.unwrap_or(5),
+                }))
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+        ChannelConfig::Email { host, port, security, username, password, from, to } => {
+            let mut message = Message::builder()
+                .from(from.parse::<Mailbox>()?)
+                .subject(notification.title_with_icon());
+            for recipient in to {
+                message = message.to(recipient.parse::<Mailbox>()?);
+            }
+            let message = message.header(ContentType::TEXT_PLAIN).body(notification.text())?;
+
+            let mut transport = match security {
+                SmtpSecurity::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+                SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+                SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+            }
+            .timeout(Some(SEND_TIMEOUT));
+            if let Some(port) = port {
+                transport = transport.port(*port);
+            }
+            if let Some(username) = username {
+                transport = transport.credentials(Credentials::new(username.clone(), password.clone().unwrap_or_default()));
+            }
+            transport.build().send(message).await?;
+        }
+    }
+    Ok(())
+}
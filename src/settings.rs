@@ -0,0 +1,231 @@
+use clap::Args;
+use std::path::PathBuf;
+
+/// Filesystem locations and external tools, configurable via CLI flags or environment
+#[derive(Debug, Clone, Args)]
+pub struct SettingsArgs {
+    #[arg(long, global = true, env = "RCLONE_GUI_DATA_DIR", default_value = "data", help = "Base directory for config, logs and database")]
+    pub data_dir: PathBuf,
+    #[arg(long, global = true, env = "RCLONE_GUI_RCLONE_CONFIG", help = "Path to rclone.conf [default: <data-dir>/cfg/rclone.conf]")]
+    pub rclone_config: Option<PathBuf>,
+    #[arg(long, global = true, env = "RCLONE_GUI_RCLONE_BINARY", default_value = "rclone", help = "rclone binary to execute")]
+    pub rclone_binary: String,
+    #[arg(long, global = true, env = "RCLONE_GUI_LOG_DIR", help = "Directory for job logs [default: <data-dir>/log]")]
+    pub log_dir: Option<PathBuf>,
+    #[arg(long, global = true, env = "RCLONE_GUI_DATABASE_URL", help = "SQLite database URL [default: sqlite:<data-dir>/tasks.db]")]
+    pub database_url: Option<String>,
+    #[arg(long, global = true, env = "RCLONE_GUI_MAX_UPLOAD_SIZE", default_value = "10G", value_parser = parse_size, help = "Largest file accepted by the upload endpoint, e.g. 500M, 10G")]
+    pub max_upload_size: u64,
+    #[arg(long, global = true, env = "RCLONE_GUI_BWLIMIT", value_parser = parse_bwlimit, help = "Bandwidth limit of jobs without their own, e.g. 10M or a timetable like \"08:00,512k 19:00,off\"")]
+    pub bwlimit: Option<String>,
+    #[arg(long, global = true, env = "RCLONE_GUI_LOG_KEEP_RUNS", default_value_t = 0, help = "Finished jobs kept per task, older ones are deleted with their logs (0 = unlimited)")]
+    pub log_keep_runs: usize,
+    #[arg(long, global = true, env = "RCLONE_GUI_LOG_KEEP_DAYS", default_value_t = 30, help = "Days finished jobs and their logs are kept (0 = unlimited)")]
+    pub log_keep_days: u64,
+    #[arg(long, global = true, env = "RCLONE_GUI_LOG_COMPRESS", help = "Gzip the logs of finished jobs")]
+    pub log_compress: bool,
+}
+
+/// Parse a size with an optional binary suffix (K, M, G, T), e.g. `512M`
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (number, multiplier) = match value.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&value[..value.len() - 1], 1u64 << 10),
+        Some('M') => (&value[..value.len() - 1], 1u64 << 20),
+        Some('G') => (&value[..value.len() - 1], 1u64 << 30),
+        Some('T') => (&value[..value.len() - 1], 1u64 << 40),
+        _ => (value, 1),
+    };
+
+    number
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid size '{}'", value))
+}
+
+/// Check a bandwidth rate as accepted by rclone: `off`, `512k`, `1.5M` or `upload:download`
+pub fn parse_bwlimit_rate(rate: &str) -> Result<String, String> {
+    let valid_size = |size: &str| {
+        let number = size.trim_end_matches(|c: char| "bBkKmMgGtTpPi".contains(c));
+        !number.is_empty() && number.parse::<f64>().is_ok_and(|n| n >= 0.0) && size.len() - number.len() <= 3
+    };
+
+    let valid = rate.eq_ignore_ascii_case("off")
+        || rate.split(':').count() <= 2 && rate.split(':').all(|part| part.eq_ignore_ascii_case("off") || valid_size(part));
+    if valid {
+        Ok(rate.to_string())
+    } else {
+        Err(format!("invalid bandwidth rate '{}'", rate))
+    }
+}
+
+/// Check a bandwidth limit: a single rate, or a timetable of `[Mon-]HH:MM,rate` entries
+/// separated by spaces, e.g. `08:00,512k 19:00,off` or `Mon-08:00,1M Sat-00:00,off`
+pub fn parse_bwlimit(value: &str) -> Result<String, String> {
+    let entries: Vec<&str> = value.split_whitespace().collect();
+    if entries.is_empty() {
+        return Err("empty bandwidth limit".to_string());
+    }
+    if entries.len() == 1 && !entries[0].contains(',') {
+        return parse_bwlimit_rate(entries[0]);
+    }
+
+    const DAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+    for entry in &entries {
+        let (time, rate) = entry
+            .split_once(',')
+            .ok_or_else(|| format!("timetable entry '{}' is not of the form HH:MM,rate", entry))?;
+        let time = match time.split_once('-') {
+            Some((day, time)) if DAYS.contains(&day.to_lowercase().as_str()) => time,
+            Some((day, _)) => return Err(format!("invalid weekday '{}' in '{}'", day, entry)),
+            None => time,
+        };
+        if chrono::NaiveTime::parse_from_str(time, "%H:%M").is_err() {
+            return Err(format!("invalid time '{}' in '{}'", time, entry));
+        }
+        parse_bwlimit_rate(rate)?;
+    }
+    Ok(entries.join(" "))
+}
+
+/// Resolved settings shared by the server, the CLI and all handlers
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub data_dir: PathBuf,
+    pub rclone_config: PathBuf,
+    pub rclone_binary: String,
+    pub log_dir: PathBuf,
+    pub database_url: String,
+    pub max_upload_size: u64,
+    pub bwlimit: Option<String>,
+    pub log_keep_runs: usize,
+    pub log_keep_days: u64,
+    pub log_compress: bool,
+}
+
+impl From<SettingsArgs> for Settings {
+    fn from(args: SettingsArgs) -> Self {
+        let data_dir = args.data_dir;
+
+        Self {
+            rclone_config: args.rclone_config.unwrap_or_else(|| data_dir.join("cfg").join("rclone.conf")),
+            rclone_binary: args.rclone_binary,
+            log_dir: args.log_dir.unwrap_or_else(|| data_dir.join("log")),
+            database_url: args
+                .database_url
+                .unwrap_or_else(|| format!("sqlite:{}", data_dir.join("tasks.db").display())),
+            max_upload_size: args.max_upload_size,
+            bwlimit: args.bwlimit,
+            log_keep_runs: args.log_keep_runs,
+            log_keep_days: args.log_keep_days,
+            log_compress: args.log_compress,
+            data_dir,
+        }
+    }
+}
+
+impl Settings {
+    /// rclone.conf as a string argument for `rclone --config`
+    pub fn rclone_config_arg(&self) -> String {
+        self.rclone_config.to_string_lossy().to_string()
+    }
+
+    /// Directory holding previous versions of rclone.conf
+    pub fn config_backup_dir(&self) -> PathBuf {
+        self.rclone_config
+            .parent()
+            .map(|parent| parent.join("backups"))
+            .unwrap_or_else(|| PathBuf::from("backups"))
+    }
+
+    /// Log file of a sync job
+    pub fn job_log_path(&self, job_id: &str) -> PathBuf {
+        self.log_dir.join(format!("{}.log", job_id))
+    }
+
+    /// List of paths a sync job is limited to (rclone `--files-from`)
+    pub fn job_files_from_path(&self, job_id: &str) -> PathBuf {
+        self.log_dir.join(format!("{}.files", job_id))
+    }
+
+    /// Compressed log file of a finished sync job
+    pub fn job_log_gz_path(&self, job_id: &str) -> PathBuf {
+        self.log_dir.join(format!("{}.log.gz", job_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_are_parsed() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("512M"), Ok(512 << 20));
+        assert_eq!(parse_size(" 2g "), Ok(2 << 30));
+        assert_eq!(parse_size("1T"), Ok(1 << 40));
+        assert_eq!(parse_size("0.5G"), Err("invalid size '0.5G'".to_string()));
+        assert_eq!(parse_size("10X"), Err("invalid size '10X'".to_string()));
+        assert_eq!(parse_size(""), Err("invalid size ''".to_string()));
+    }
+
+    #[test]
+    fn oversized_sizes_are_rejected() {
+        assert_eq!(parse_size("16777215T"), Ok(16777215 << 40));
+        assert_eq!(parse_size("16777216T"), Err("invalid size '16777216T'".to_string()));
+        assert!(parse_size("18446744073709551616").is_err());
+    }
+
+    #[test]
+    fn single_rates_are_accepted() {
+        assert_eq!(parse_bwlimit("10M"), Ok("10M".to_string()));
+        assert_eq!(parse_bwlimit(" 1.5Mi "), Ok("1.5Mi".to_string()));
+        assert_eq!(parse_bwlimit("off"), Ok("off".to_string()));
+        assert_eq!(parse_bwlimit_rate("OFF"), Ok("OFF".to_string()));
+    }
+
+    #[test]
+    fn upload_and_download_rates_are_accepted() {
+        assert_eq!(parse_bwlimit("10M:512k"), Ok("10M:512k".to_string()));
+        assert_eq!(parse_bwlimit_rate("off:1M"), Ok("off:1M".to_string()));
+        assert!(parse_bwlimit_rate("1M:2M:3M").is_err());
+        assert!(parse_bwlimit_rate("1M:").is_err());
+    }
+
+    #[test]
+    fn bad_units_are_rejected() {
+        assert_eq!(parse_bwlimit("10X"), Err("invalid bandwidth rate '10X'".to_string()));
+        assert!(parse_bwlimit_rate("M").is_err());
+        assert!(parse_bwlimit_rate("-1M").is_err());
+        assert!(parse_bwlimit_rate("1MiBB").is_err());
+    }
+
+    #[test]
+    fn timetables_are_accepted() {
+        assert_eq!(parse_bwlimit("08:00,512k   19:00,off"), Ok("08:00,512k 19:00,off".to_string()));
+        assert_eq!(
+            parse_bwlimit("Mon-08:00,1M sat-00:00,off Sun-12:30,2M:1M"),
+            Ok("Mon-08:00,1M sat-00:00,off Sun-12:30,2M:1M".to_string())
+        );
+    }
+
+    #[test]
+    fn bad_timetables_are_rejected() {
+        assert_eq!(parse_bwlimit("25:00,1M"), Err("invalid time '25:00' in '25:00,1M'".to_string()));
+        assert_eq!(parse_bwlimit("Xyz-08:00,1M"), Err("invalid weekday 'Xyz' in 'Xyz-08:00,1M'".to_string()));
+        assert_eq!(
+            parse_bwlimit("08:00,1M 19:00"),
+            Err("timetable entry '19:00' is not of the form HH:MM,rate".to_string())
+        );
+        assert_eq!(parse_bwlimit("08:00,fast"), Err("invalid bandwidth rate 'fast'".to_string()));
+    }
+
+    #[test]
+    fn empty_limits_are_rejected() {
+        assert_eq!(parse_bwlimit(""), Err("empty bandwidth limit".to_string()));
+        assert_eq!(parse_bwlimit("   "), Err("empty bandwidth limit".to_string()));
+        assert!(parse_bwlimit_rate("").is_err());
+    }
+}
@@ -4,6 +4,8 @@ let currentRemotePath = '/';
 let selectedRemotePath = '/';  // Der aktuell ausgewählte Ordner für den Upload
 let currentSyncSource = '';
 let currentSyncJobId = '';
+let loadedFiles = [];  // Entries of the current local folder loaded so far
+let totalFiles = 0;    // Number of entries in the current local folder (X-Total-Count)
 let configs = [];
 let tasks = [];
 
@@ -191,15 +193,20 @@ async function deleteConfig(name) {
 }
 
 // File browser functions
-async function loadFiles(path = currentPath) {
+const FILE_PAGE_SIZE = 200;
+
+async function loadFiles(path = currentPath, append = false) {
     currentPath = path;
+    const offset = append ? loadedFiles.length : 0;
     
     try {
-        const response = await fetch(`/api/files/local?path=${encodeURIComponent(path)}`);
+        const response = await fetch(`/api/files/local?path=${encodeURIComponent(path)}&offset=${offset}&limit=${FILE_PAGE_SIZE}`);
         const result = await response.json();
         
         if (result.success) {
-            displayFiles(result.data);
+            loadedFiles = append ? loadedFiles.concat(result.data) : result.data;
+            totalFiles = parseInt(response.headers.get('x-total-count'), 10) || loadedFiles.length;
+            displayFiles(loadedFiles);
             updateBreadcrumb(path);
         } else {
             showAlert('files-alert', 'Error loading files: ' + result.error, 'error');
@@ -209,6 +216,10 @@ async function loadFiles(path = currentPath) {
     }
 }
 
+function loadMoreFiles() {
+    loadFiles(currentPath, true);
+}
+
 function displayFiles(files) {
     const fileList = document.getElementById('file-list');
     
@@ -217,6 +228,12 @@ function displayFiles(files) {
         return;
     }
     
+    const loadMore = files.length < totalFiles ? `
+        <div class="text-center py-2">
+            <button class="btn btn-ghost btn-sm" onclick="loadMoreFiles()">Load more (${files.length} of ${totalFiles})</button>
+        </div>
+    ` : '';
+    
     fileList.innerHTML = files.map(file => {
         const sizeInfo = file.size ? formatBytes(file.size) : '';
         const fileTypeInfo = file.is_dir ? 'Folder' : (sizeInfo ? `File · ${sizeInfo}` : 'File');
@@ -244,7 +261,7 @@ function displayFiles(files) {
             </div>
         </div>
         `;
-    }).join('');
+    }).join('') + loadMore;
 }
 
 function updateBreadcrumb(path) {
@@ -432,14 +449,32 @@ async function loadRemoteFiles(remotePath = '/') {
     }
 }
 
+// Load the folders of a remote directory. Listings are paged and directories come first,
+// so paging stops at the first file.
+async function fetchRemoteFolders(remoteName, remotePath) {
+    const folders = [];
+    for (let offset = 0; ; offset += FILE_PAGE_SIZE) {
+        const response = await fetch(`/api/files/remote?remote=${remoteName}&path=${encodeURIComponent(remotePath)}&offset=${offset}&limit=${FILE_PAGE_SIZE}`);
+        const result = await response.json();
+        if (!result.success) {
+            return result;
+        }
+        
+        const pageFolders = result.data.filter(file => file.is_dir);
+        folders.push(...pageFolders);
+        const total = parseInt(response.headers.get('x-total-count'), 10) || 0;
+        if (pageFolders.length < result.data.length || offset + FILE_PAGE_SIZE >= total) {
+            return { success: true, data: folders };
+        }
+    }
+}
+
 async function loadRemoteFilesFromServer(remoteName, remotePath) {
     try {
-        const response = await fetch(`/api/files/remote?remote=${remoteName}&path=${encodeURIComponent(remotePath)}`);
-        const result = await response.json();
+        const result = await fetchRemoteFolders(remoteName, remotePath);
         
         if (result.success) {
-            // Filter only directories for our use case
-            const folders = result.data.filter(file => file.is_dir);
+            const folders = result.data;
             
             // Save to cache
             saveRemoteFolderCache(remoteName, remotePath, folders);
@@ -459,11 +494,10 @@ async function loadRemoteFilesFromServer(remoteName, remotePath) {
 
 async function loadRemoteFilesInBackground(remoteName, remotePath) {
     try {
-        const response = await fetch(`/api/files/remote?remote=${remoteName}&path=${encodeURIComponent(remotePath)}`);
-        const result = await response.json();
+        const result = await fetchRemoteFolders(remoteName, remotePath);
         
         if (result.success) {
-            const folders = result.data.filter(file => file.is_dir);
+            const folders = result.data;
             
             // Update cache with fresh data
             saveRemoteFolderCache(remoteName, remotePath, folders);
@@ -700,7 +734,7 @@ function updateProgressDisplay(progress) {
     // Update icon based on status
     if (progress.status === 'Completed') {
         setProgressModalIcon('completed');
-    } else if (progress.status === 'Failed' || progress.status.includes('Error')) {
+    } else if (progress.status.startsWith('Failed') || progress.status.startsWith('Error')) {
         setProgressModalIcon('error');
     } else {
         setProgressModalIcon('loading');
@@ -730,7 +764,7 @@ function displaySyncJobs(jobs) {
     
     syncJobsDiv.innerHTML = jobs.map(job => {
         const statusColor = job.status === 'Completed' ? 'badge-success' : 
-                           job.status === 'Failed' ? 'badge-error' : 
+                           job.status.startsWith('Failed') || job.status.startsWith('Error') ? 'badge-error' : 
                            job.status === 'Running' ? 'badge-warning' : 'badge-info';
         
         // Calculate elapsed time - use end_time if available, otherwise current time
@@ -747,7 +781,7 @@ function displaySyncJobs(jobs) {
         }
         
         // Add action buttons for completed/failed jobs
-        const isCompleted = job.status === 'Completed' || job.status === 'Failed' || job.status.includes('Error');
+        const isCompleted = job.status === 'Completed' || job.status === 'Cancelled' || job.status.startsWith('Failed') || job.status.startsWith('Error');
         const actionButtons = isCompleted ? `
             <div class="flex items-center space-x-2 mt-3">
                 <button onclick="viewSyncLog('${job.id}')" class="btn btn-info btn-sm">